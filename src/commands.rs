@@ -1,49 +1,207 @@
-use crate::{ContainerInfo, Image, StreamType, WaitError, WaitFor};
-use std::{collections::HashMap, process::Command as StdCommand, process::Stdio, time::Duration};
+use crate::{
+    naming, tar, ContainerInfo, DockerError, ExposedPort, HostBinding, Image, ImageInfo, IpVersion, NetworkOptions,
+    PullProgress, StreamType, VolumeOptions, WaitError, WaitFor,
+};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::{Command as StdCommand, Output as StdOutput, Stdio},
+    time::Duration,
+};
 use tokio::{
-    io::{AsyncBufReadExt, BufReader},
-    process::Command,
-    stream::StreamExt,
+    io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader},
+    process::{Child, ChildStderr, ChildStdin, ChildStdout, Command},
     time::timeout,
 };
 
+/// The captured result of a process that ran to completion inside a docker container.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Output {
+    pub exit_code: i32,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+impl Output {
+    /// Returns `true` if the process exited with code `0`.
+    pub fn success(&self) -> bool {
+        self.exit_code == 0
+    }
+
+    /// Returns the captured stdout, replacing invalid UTF-8 sequences.
+    pub fn stdout_lossy(&self) -> String {
+        String::from_utf8_lossy(&self.stdout).into_owned()
+    }
+
+    /// Returns the captured stderr, replacing invalid UTF-8 sequences.
+    pub fn stderr_lossy(&self) -> String {
+        String::from_utf8_lossy(&self.stderr).into_owned()
+    }
+}
+
+/// The exit code `docker run` uses when the docker daemon itself failed.
+const DOCKER_RUN_DAEMON_ERROR: i32 = 125;
+
+/// How the docker CLI starts the message when the docker daemon rejected a request.
+const DAEMON_ERROR_PREFIX: &str = "Error response from daemon";
+
 pub struct RunCommand;
 
 impl RunCommand {
-    pub async fn create_container<I: Image>(image: &I) -> String {
+    pub async fn create_container<I: Image>(image: &I) -> Result<String, DockerError> {
+        let mut command = Command::new("docker");
+        command.arg("run");
+        RunCommand::add_container_options(&mut command, image)?;
+        RunCommand::check_platform(image).await?;
+        command.arg("-d"); // Always run detached
+        if image.publish_all_ports() {
+            command.arg("-P");
+        }
+        command.arg(image.descriptor()).args(image.args());
+        let output = execute(&mut command).await?;
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+    }
+
+    /// Runs the image in the foreground, captures both output streams and removes the container afterwards.
+    pub async fn run_once<I: Image>(image: &I) -> Result<Output, DockerError> {
         let mut command = Command::new("docker");
         command.arg("run");
+        RunCommand::add_container_options(&mut command, image)?;
+        RunCommand::check_platform(image).await?;
+        command
+            .arg("--rm")
+            .arg(image.descriptor())
+            .args(image.args())
+            .stdin(Stdio::null())
+            .kill_on_drop(true);
+
+        log::debug!("Executing command: {:?}", command);
+        let output = command.output().await?;
+        match output.status.code() {
+            Some(code) if code != DOCKER_RUN_DAEMON_ERROR => Ok(Output {
+                exit_code: code,
+                stdout: output.stdout,
+                stderr: output.stderr,
+            }),
+            code => Err(DockerError::CommandFailed {
+                command: format!("{:?}", command),
+                code,
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            }),
+        }
+    }
+
+    /// Fails if the image is present locally for a different platform than the requested one, which docker would
+    /// otherwise either run under the wrong architecture or reject with a less helpful message.
+    async fn check_platform<I: Image>(image: &I) -> Result<(), DockerError> {
+        match image.platform() {
+            Some(platform) => ImageInspectCommand::check_platform(&image.descriptor(), &platform).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Adds the options shared by all ways of running an image.
+    fn add_container_options<I: Image>(command: &mut Command, image: &I) -> Result<(), DockerError> {
+        if let Some(platform) = image.platform() {
+            command.arg("--platform").arg(platform);
+        }
         // Environment variables
         for (key, value) in image.env_vars() {
             command.arg("-e").arg(format!("{}={}", key, value));
         }
         // Mounts
-        for value in image.mounts() {
-            command.arg("--mount").arg(
-                value
-                    .iter()
-                    .map(|(key, value)| format!("{}={}", key, value))
-                    .collect::<Vec<_>>()
-                    .join(","),
-            );
+        for mount in image.mounts() {
+            command.arg("--mount").arg(mount.to_arg()?);
         }
         // Network
         if let Some(network) = image.network() {
             command.arg("--network").arg(network);
+            for alias in image.network_aliases() {
+                command.arg("--network-alias").arg(alias);
+            }
         }
-        command
-            .arg("-d") // Always run detached
-            .arg("-P") // Always expose all ports
-            .arg(image.descriptor())
-            .args(image.args())
-            .stdout(Stdio::piped());
+        // Name resolution
+        for (hostname, address) in image.extra_hosts() {
+            command.arg("--add-host").arg(format!("{}:{}", hostname, address));
+        }
+        for server in image.dns() {
+            command.arg("--dns").arg(server);
+        }
+        for domain in image.dns_search() {
+            command.arg("--dns-search").arg(domain);
+        }
+        // Identity
+        if let Some(name) = image.container_name() {
+            command.arg("--name").arg(naming::unique_name(&name));
+        }
+        if let Some(hostname) = image.hostname() {
+            command.arg("--hostname").arg(hostname);
+        }
+        for (key, value) in naming::default_labels() {
+            command.arg("--label").arg(format!("{}={}", key, value));
+        }
+        for (key, value) in image.labels() {
+            command.arg("--label").arg(format!("{}={}", key, value));
+        }
+        // Process
+        if let Some(entrypoint) = image.entrypoint() {
+            command.arg("--entrypoint").arg(entrypoint);
+        }
+        if let Some(workdir) = image.workdir() {
+            command.arg("--workdir").arg(workdir);
+        }
+        if let Some(user) = image.user() {
+            command.arg("--user").arg(user);
+        }
+        if image.init() {
+            command.arg("--init");
+        }
+        // Restart and shutdown
+        if let Some(restart_policy) = image.restart_policy() {
+            command.arg("--restart").arg(restart_policy.to_arg());
+        }
+        if let Some(signal) = image.stop_signal() {
+            command.arg("--stop-signal").arg(signal);
+        }
+        if let Some(timeout) = image.stop_timeout() {
+            command.arg("--stop-timeout").arg(whole_seconds(timeout).to_string());
+        }
+        // Resources and security
+        command.args(image.resource_limits().to_args()?);
+        command.args(image.security_options().to_args());
+        for (key, value) in image.sysctls() {
+            command.arg("--sysctl").arg(format!("{}={}", key, value));
+        }
+        // Ports
+        for port in image.exposed_ports() {
+            command.arg("--expose").arg(port.to_string());
+        }
+        for binding in image.port_bindings() {
+            command.arg("-p").arg(binding.to_arg());
+        }
+        // Stdin
+        if image.stdin_open() {
+            command.arg("-i");
+        }
+        Ok(())
+    }
+}
 
-        log::debug!("Executing command: {:?}", command);
-        let child = command.spawn().expect("Failed to execute docker run command");
-        let stdout = child.stdout.expect("failed to unwrap stdout docker run command");
-        let reader = BufReader::new(stdout);
-        let container_id = reader.lines().next().await.unwrap().unwrap();
-        container_id
+pub struct CreateCommand;
+
+impl CreateCommand {
+    /// Creates a container for the image without starting it.
+    pub async fn create_container<I: Image>(image: &I) -> Result<String, DockerError> {
+        let mut command = Command::new("docker");
+        command.arg("create");
+        RunCommand::add_container_options(&mut command, image)?;
+        RunCommand::check_platform(image).await?;
+        if image.publish_all_ports() {
+            command.arg("-P");
+        }
+        command.arg(image.descriptor()).args(image.args());
+        let output = execute(&mut command).await?;
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
     }
 }
 
@@ -210,20 +368,48 @@ impl LogsCommand {
 /// The exposed ports of a running container.
 #[derive(Debug, PartialEq, Default)]
 pub struct Ports {
-    mapping: HashMap<u16, u16>,
+    mapping: HashMap<ExposedPort, Vec<HostBinding>>,
 }
 
 impl Ports {
-    /// Registers the mapping of an exposed port.
-    pub fn add_mapping(&mut self, internal: u16, host: u16) -> &mut Self {
-        log::debug!("Registering port mapping: {} -> {}", internal, host);
-        self.mapping.insert(internal, host);
+    /// Registers a host binding of an exposed port, a port can be bound on several host interfaces.
+    pub fn add_binding(&mut self, internal: ExposedPort, host: HostBinding) -> &mut Self {
+        log::debug!("Registering port mapping: {} -> {}:{}", internal, host.ip, host.port);
+        self.mapping.entry(internal).or_default().push(host);
         self
     }
 
-    /// Returns the host port for the given internal port.
+    /// Returns the host port for the given internal TCP port, preferring a binding on an IPv4 interface.
     pub fn map_to_host_port(&self, internal_port: u16) -> Option<u16> {
-        self.mapping.get(&internal_port).cloned()
+        self.map_to_host_port_preferring(ExposedPort::tcp(internal_port), IpVersion::V4)
+    }
+
+    /// Returns the host port for the given internal UDP port, preferring a binding on an IPv4 interface.
+    pub fn map_to_host_port_udp(&self, internal_port: u16) -> Option<u16> {
+        self.map_to_host_port_preferring(ExposedPort::udp(internal_port), IpVersion::V4)
+    }
+
+    /// Returns the host port for the given internal port, preferring a binding on an interface of the given IP
+    /// version and falling back to any other binding.
+    pub fn map_to_host_port_preferring(&self, internal: ExposedPort, ip_version: IpVersion) -> Option<u16> {
+        let bindings = self.bindings(internal);
+        bindings
+            .iter()
+            .find(|binding| binding.ip_version() == ip_version)
+            .or_else(|| bindings.first())
+            .map(|binding| binding.port)
+    }
+
+    /// Returns all host bindings of the given internal port.
+    pub fn bindings(&self, internal: ExposedPort) -> &[HostBinding] {
+        self.mapping.get(&internal).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Iterates over all exposed ports that are bound on the host, together with their host bindings.
+    pub fn iter(&self) -> impl Iterator<Item = (ExposedPort, &[HostBinding])> {
+        self.mapping
+            .iter()
+            .map(|(internal, bindings)| (*internal, bindings.as_slice()))
     }
 }
 
@@ -231,22 +417,26 @@ pub struct InspectCommand;
 
 impl InspectCommand {
     pub async fn get_container_info(container_id: &str) -> ContainerInfo {
-        let child = Command::new("docker")
-            .arg("inspect")
-            .arg(container_id)
-            .stdout(Stdio::piped())
-            .spawn()
-            .expect("failed to spawn docker inspect command");
-        let stdout = child.stdout.expect("failed to unwrap stdout docker inspect command");
-        let mut reader = BufReader::new(stdout).lines();
-        let mut buffer = String::new();
-        while let Some(line) = reader.next_line().await.unwrap() {
-            buffer.push_str(&line);
+        InspectCommand::inspect_container(container_id)
+            .await
+            .expect("failed to run docker inspect command")
+    }
+
+    pub async fn inspect_container(container_id: &str) -> Result<ContainerInfo, DockerError> {
+        let mut command = Command::new("docker");
+        command.arg("inspect").arg("--type").arg("container").arg(container_id);
+        let output = execute(&mut command).await?;
+        let mut infos = serde_json::from_slice::<Vec<ContainerInfo>>(&output.stdout)
+            .map_err(|e| DockerError::UnexpectedOutput(format!("failed to parse docker inspect output: {}", e)))?;
+        if infos.is_empty() {
+            return Err(DockerError::UnexpectedOutput(format!(
+                "no container {} found",
+                container_id
+            )));
         }
-        let mut infos = serde_json::from_str::<Vec<ContainerInfo>>(&buffer).unwrap();
         let info = infos.remove(0);
         log::trace!("Fetched container info: {:#?}", info);
-        info
+        Ok(info)
     }
 
     pub async fn get_container_ports(container_id: &str) -> Ports {
@@ -254,6 +444,100 @@ impl InspectCommand {
     }
 }
 
+pub struct ImageInspectCommand;
+
+impl ImageInspectCommand {
+    /// Returns the details of a local image, `None` if it has not been pulled or built yet.
+    pub async fn inspect_image(descriptor: &str) -> Result<Option<ImageInfo>, DockerError> {
+        let mut command = Command::new("docker");
+        command.arg("inspect").arg("--type").arg("image").arg(descriptor);
+        let output = match execute(&mut command).await {
+            Ok(output) => output,
+            Err(DockerError::CommandFailed { ref stderr, .. }) if stderr.contains("No such image") => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let mut infos = serde_json::from_slice::<Vec<ImageInfo>>(&output.stdout)
+            .map_err(|e| DockerError::UnexpectedOutput(format!("failed to parse docker inspect output: {}", e)))?;
+        if infos.is_empty() {
+            return Ok(None);
+        }
+        let info = infos.remove(0);
+        log::trace!("Fetched image info: {:#?}", info);
+        Ok(Some(info))
+    }
+
+    /// Fails with [`DockerError::PlatformMismatch`] if the local image does not match the platform.
+    ///
+    /// Images that are not present locally pass the check, docker pulls them for the requested platform.
+    pub async fn check_platform(descriptor: &str, platform: &str) -> Result<(), DockerError> {
+        match ImageInspectCommand::inspect_image(descriptor).await? {
+            Some(info) if !info.matches_platform(platform) => Err(DockerError::PlatformMismatch {
+                image: descriptor.to_owned(),
+                requested: platform.to_owned(),
+                actual: info.platform(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+pub struct PullCommand;
+
+impl PullCommand {
+    /// Pulls the image and reports every line of progress docker prints while doing so.
+    pub async fn pull<F>(descriptor: &str, platform: Option<&str>, mut on_progress: F) -> Result<(), DockerError>
+    where
+        F: FnMut(PullProgress),
+    {
+        let mut command = Command::new("docker");
+        command.arg("pull");
+        if let Some(platform) = platform {
+            command.arg("--platform").arg(platform);
+        }
+        command
+            .arg(descriptor)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        log::debug!("Executing command: {:?}", command);
+        let mut child = command.spawn()?;
+        let stdout = child
+            .stdout
+            .take()
+            .expect("failed to unwrap stdout docker pull command");
+        let mut stderr = child
+            .stderr
+            .take()
+            .expect("failed to unwrap stderr docker pull command");
+
+        let progress = async {
+            let mut reader = BufReader::new(stdout).lines();
+            while let Some(line) = reader.next_line().await? {
+                if !line.trim().is_empty() {
+                    on_progress(PullProgress::parse(&line));
+                }
+            }
+            Ok::<_, DockerError>(())
+        };
+        let mut errors = String::new();
+        let (progress, read_errors) = tokio::join!(progress, stderr.read_to_string(&mut errors));
+        progress?;
+        read_errors?;
+
+        let status = child.await?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(DockerError::CommandFailed {
+                command: format!("{:?}", command),
+                code: status.code(),
+                stderr: errors,
+            })
+        }
+    }
+}
+
 pub struct RmCommand;
 
 impl RmCommand {
@@ -272,12 +556,614 @@ impl RmCommand {
 pub struct StopCommand;
 
 impl StopCommand {
-    pub fn stop_container(container_id: &str) {
-        StdCommand::new("docker")
-            .arg("stop")
+    /// Stops the container, killing it if it did not exit within the given timeout or the one it was configured with.
+    pub fn stop_container(container_id: &str, timeout: Option<Duration>) {
+        let mut command = StdCommand::new("docker");
+        command.arg("stop");
+        if let Some(timeout) = timeout {
+            command.arg("-t").arg(whole_seconds(timeout).to_string());
+        }
+        command
             .arg(container_id)
             .stdout(Stdio::piped())
             .status()
             .expect("failed to run docker stop command");
     }
+
+    pub async fn stop_container_with_timeout(container_id: &str, timeout: Duration) -> Result<(), DockerError> {
+        let mut command = Command::new("docker");
+        command
+            .arg("stop")
+            .arg("-t")
+            .arg(whole_seconds(timeout).to_string())
+            .arg(container_id);
+        execute(&mut command).await?;
+        Ok(())
+    }
+}
+
+/// Rounds the timeout up to the whole seconds docker expects, so a sub-second grace period does not become none.
+fn whole_seconds(timeout: Duration) -> u64 {
+    timeout.as_secs() + u64::from(timeout.subsec_nanos() > 0)
+}
+
+pub struct NetworkCommand;
+
+impl NetworkCommand {
+    /// Creates a network with the given name and returns its id.
+    pub async fn create_network(name: &str, options: &NetworkOptions) -> Result<String, DockerError> {
+        let mut command = Command::new("docker");
+        command.arg("network").arg("create");
+        if let Some(driver) = options.driver() {
+            command.arg("--driver").arg(driver);
+        }
+        if let Some(subnet) = options.subnet() {
+            command.arg("--subnet").arg(subnet);
+        }
+        if let Some(gateway) = options.gateway() {
+            command.arg("--gateway").arg(gateway);
+        }
+        if options.is_internal() {
+            command.arg("--internal");
+        }
+        for (key, value) in naming::default_labels() {
+            command.arg("--label").arg(format!("{}={}", key, value));
+        }
+        for (key, value) in options.labels() {
+            command.arg("--label").arg(format!("{}={}", key, value));
+        }
+        command.arg(name);
+        let output = execute(&mut command).await?;
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+    }
+
+    pub fn rm_network(name: &str) {
+        StdCommand::new("docker")
+            .arg("network")
+            .arg("rm")
+            .arg(name)
+            .stdout(Stdio::piped())
+            .status()
+            .expect("failed to run docker network rm command");
+    }
+
+    pub async fn connect_container(network: &str, container_id: &str, aliases: &[&str]) -> Result<(), DockerError> {
+        let mut command = Command::new("docker");
+        command.arg("network").arg("connect");
+        for alias in aliases {
+            command.arg("--alias").arg(alias);
+        }
+        command.arg(network).arg(container_id);
+        execute(&mut command).await?;
+        Ok(())
+    }
+
+    pub async fn disconnect_container(network: &str, container_id: &str) -> Result<(), DockerError> {
+        let mut command = Command::new("docker");
+        command.arg("network").arg("disconnect").arg(network).arg(container_id);
+        execute(&mut command).await?;
+        Ok(())
+    }
+}
+
+pub struct VolumeCommand;
+
+impl VolumeCommand {
+    pub async fn create_volume(name: &str, options: &VolumeOptions) -> Result<(), DockerError> {
+        let mut command = Command::new("docker");
+        command.arg("volume").arg("create");
+        if let Some(driver) = options.driver() {
+            command.arg("--driver").arg(driver);
+        }
+        for (key, value) in options.driver_opts() {
+            command.arg("--opt").arg(format!("{}={}", key, value));
+        }
+        for (key, value) in naming::default_labels() {
+            command.arg("--label").arg(format!("{}={}", key, value));
+        }
+        for (key, value) in options.labels() {
+            command.arg("--label").arg(format!("{}={}", key, value));
+        }
+        command.arg(name);
+        execute(&mut command).await?;
+        Ok(())
+    }
+
+    pub fn rm_volume(name: &str) {
+        StdCommand::new("docker")
+            .arg("volume")
+            .arg("rm")
+            .arg(name)
+            .stdout(Stdio::piped())
+            .status()
+            .expect("failed to run docker volume rm command");
+    }
+}
+
+pub struct StartCommand;
+
+impl StartCommand {
+    pub async fn start_container(container_id: &str) -> Result<(), DockerError> {
+        let mut command = Command::new("docker");
+        command.arg("start").arg(container_id);
+        execute(&mut command).await?;
+        Ok(())
+    }
+}
+
+pub struct RestartCommand;
+
+impl RestartCommand {
+    pub async fn restart_container(container_id: &str) -> Result<(), DockerError> {
+        let mut command = Command::new("docker");
+        command.arg("restart").arg(container_id);
+        execute(&mut command).await?;
+        Ok(())
+    }
+}
+
+pub struct PauseCommand;
+
+impl PauseCommand {
+    pub async fn pause_container(container_id: &str) -> Result<(), DockerError> {
+        let mut command = Command::new("docker");
+        command.arg("pause").arg(container_id);
+        execute(&mut command).await?;
+        Ok(())
+    }
+
+    pub async fn unpause_container(container_id: &str) -> Result<(), DockerError> {
+        let mut command = Command::new("docker");
+        command.arg("unpause").arg(container_id);
+        execute(&mut command).await?;
+        Ok(())
+    }
+}
+
+pub struct KillCommand;
+
+impl KillCommand {
+    pub async fn kill_container(container_id: &str, signal: &str) -> Result<(), DockerError> {
+        let mut command = Command::new("docker");
+        command.arg("kill").arg("-s").arg(signal).arg(container_id);
+        execute(&mut command).await?;
+        Ok(())
+    }
+}
+
+/// The exit status of the main process of a docker container.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ExitStatus {
+    code: i32,
+}
+
+impl ExitStatus {
+    pub fn new(code: i32) -> Self {
+        Self { code }
+    }
+
+    /// Returns the exit code of the process.
+    pub fn code(&self) -> i32 {
+        self.code
+    }
+
+    /// Returns `true` if the process exited with code `0`.
+    pub fn success(&self) -> bool {
+        self.code == 0
+    }
+}
+
+pub struct WaitCommand;
+
+impl WaitCommand {
+    /// Blocks until the container stops and returns the exit status of its main process.
+    pub async fn wait_container(container_id: &str, wait_duration: Duration) -> Result<ExitStatus, DockerError> {
+        let mut command = Command::new("docker");
+        command.arg("wait").arg(container_id).kill_on_drop(true);
+        let output = match timeout(wait_duration, execute(&mut command)).await {
+            Ok(output) => output?,
+            Err(_) => {
+                log::error!("Container {} did not exit within {:?}", container_id, wait_duration);
+                return Err(DockerError::Timeout);
+            }
+        };
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        stdout
+            .trim()
+            .parse()
+            .map(ExitStatus::new)
+            .map_err(|_| DockerError::UnexpectedOutput(stdout.into_owned()))
+    }
+}
+
+/// Describes a command to run inside a running docker container.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ExecRequest {
+    pub cmd: Vec<String>,
+    pub env: HashMap<String, String>,
+    pub user: Option<String>,
+    pub workdir: Option<String>,
+    pub tty: bool,
+}
+
+impl ExecRequest {
+    pub fn new<S: Into<String>>(cmd: Vec<S>) -> Self {
+        Self {
+            cmd: cmd.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// A process running inside a docker container whose streams are used while it runs.
+pub struct ExecProcess {
+    child: Child,
+}
+
+impl ExecProcess {
+    /// Takes the stdin stream of the process, returns `None` if it was already taken or stdin is not attached.
+    ///
+    /// Dropping the returned stream closes it, which signals end of input to the process while its output can
+    /// still be read.
+    pub fn take_stdin(&mut self) -> Option<ChildStdin> {
+        self.child.stdin.take()
+    }
+
+    /// Closes the stdin stream of the process if it was not taken, signalling end of input.
+    pub fn close_stdin(&mut self) {
+        drop(self.take_stdin());
+    }
+
+    /// Takes the stdout stream of the process, returns `None` if it was already taken.
+    pub fn take_stdout(&mut self) -> Option<ChildStdout> {
+        self.child.stdout.take()
+    }
+
+    /// Takes the stderr stream of the process, returns `None` if it was already taken.
+    pub fn take_stderr(&mut self) -> Option<ChildStderr> {
+        self.child.stderr.take()
+    }
+
+    /// Waits for the process to exit.
+    ///
+    /// Like [`std::process::Child::wait`], stdin is closed first if it was not taken, so processes reading their
+    /// input until its end can finish. Output on streams that were not taken is read and discarded meanwhile, so the
+    /// process neither blocks on a full pipe nor gets killed by writing to a closed one.
+    pub async fn wait(mut self) -> Result<ExitStatus, DockerError> {
+        self.close_stdin();
+        let stdout = self.take_stdout();
+        let stderr = self.take_stderr();
+        let (status, stdout, stderr) = tokio::join!(self.child, discard(stdout), discard(stderr));
+        stdout?;
+        stderr?;
+        let status = status?;
+        status
+            .code()
+            .map(ExitStatus::new)
+            .ok_or_else(|| DockerError::UnexpectedOutput(format!("process terminated by signal: {:?}", status)))
+    }
+}
+
+/// Reads the stream to its end, throwing the data away.
+async fn discard<R: AsyncRead + Unpin>(stream: Option<R>) -> Result<(), DockerError> {
+    if let Some(mut stream) = stream {
+        tokio::io::copy(&mut stream, &mut tokio::io::sink()).await?;
+    }
+    Ok(())
+}
+
+pub struct ExecCommand;
+
+impl ExecCommand {
+    /// Runs the command inside the container and captures its output once it exited.
+    ///
+    /// A non-zero exit code of the command is part of the [`Output`], failing to run it at all, e.g. because the
+    /// container is not running, is a [`DockerError::CommandFailed`].
+    pub async fn exec(container_id: &str, request: &ExecRequest) -> Result<Output, DockerError> {
+        let mut command = ExecCommand::command(container_id, request, false);
+        command.stdin(Stdio::null()).kill_on_drop(true);
+        log::debug!("Executing command: {:?}", command);
+        let output = command.output().await?;
+        // `docker exec` exits with 1 when the daemon rejects the request, e.g. because the container is not running,
+        // which is only distinguishable from the command exiting with 1 by the message it prints.
+        let daemon_error = output.stderr.starts_with(DAEMON_ERROR_PREFIX.as_bytes());
+        match output.status.code() {
+            Some(exit_code) if exit_code == 0 || !daemon_error => Ok(Output {
+                exit_code,
+                stdout: output.stdout,
+                stderr: output.stderr,
+            }),
+            code => Err(DockerError::CommandFailed {
+                command: format!("{:?}", command),
+                code,
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            }),
+        }
+    }
+
+    /// Starts the command inside the container and hands out its output streams while it runs.
+    pub fn spawn(container_id: &str, request: &ExecRequest) -> Result<ExecProcess, DockerError> {
+        let mut command = ExecCommand::command(container_id, request, false);
+        command.stdin(Stdio::null());
+        spawn_attached(command)
+    }
+
+    /// Starts the command inside the container with its stdin kept open and hands out all of its streams.
+    pub fn spawn_interactive(container_id: &str, request: &ExecRequest) -> Result<ExecProcess, DockerError> {
+        let mut command = ExecCommand::command(container_id, request, true);
+        command.stdin(Stdio::piped());
+        spawn_attached(command)
+    }
+
+    fn command(container_id: &str, request: &ExecRequest, interactive: bool) -> Command {
+        let mut command = Command::new("docker");
+        command.arg("exec");
+        if interactive {
+            command.arg("-i");
+        }
+        for (key, value) in &request.env {
+            command.arg("-e").arg(format!("{}={}", key, value));
+        }
+        if let Some(user) = &request.user {
+            command.arg("-u").arg(user);
+        }
+        if let Some(workdir) = &request.workdir {
+            command.arg("-w").arg(workdir);
+        }
+        if request.tty {
+            command.arg("-t");
+        }
+        command.arg(container_id).args(&request.cmd);
+        command
+    }
+}
+
+pub struct AttachCommand;
+
+impl AttachCommand {
+    /// Attaches to the stdin, stdout and stderr of the main process of the container.
+    ///
+    /// Signals are not proxied, so dropping the returned process only detaches from the container.
+    pub fn attach(container_id: &str) -> Result<ExecProcess, DockerError> {
+        let mut command = Command::new("docker");
+        command
+            .arg("attach")
+            .arg("--sig-proxy=false")
+            .arg(container_id)
+            .stdin(Stdio::piped());
+        spawn_attached(command)
+    }
+}
+
+/// Something to copy into a docker container.
+#[derive(Debug, PartialEq, Clone)]
+pub enum CopySource {
+    /// A file or directory on the host, directories are copied recursively keeping the file modes.
+    Path { path: PathBuf, preserve_owner: bool },
+    /// The contents of a single file.
+    Bytes {
+        data: Vec<u8>,
+        mode: u32,
+        uid: u32,
+        gid: u32,
+    },
+}
+
+impl CopySource {
+    /// Copies a file or directory from the host, owned by root inside the container.
+    pub fn path<P: Into<PathBuf>>(path: P) -> Self {
+        CopySource::Path {
+            path: path.into(),
+            preserve_owner: false,
+        }
+    }
+
+    /// Copies in-memory contents as a file with mode `0644`, owned by root inside the container.
+    pub fn bytes<D: Into<Vec<u8>>>(data: D) -> Self {
+        CopySource::Bytes {
+            data: data.into(),
+            mode: 0o644,
+            uid: 0,
+            gid: 0,
+        }
+    }
+
+    /// Keeps the host user and group ids of the copied files.
+    ///
+    /// Only applies to [`CopySource::Path`].
+    pub fn with_preserved_owner(self) -> Self {
+        match self {
+            CopySource::Path { path, .. } => CopySource::Path {
+                path,
+                preserve_owner: true,
+            },
+            bytes => bytes,
+        }
+    }
+
+    /// Sets the file mode, e.g. `0o755` for an executable script.
+    ///
+    /// Only applies to [`CopySource::Bytes`], files copied from the host keep their mode.
+    pub fn with_mode(self, mode: u32) -> Self {
+        match self {
+            CopySource::Bytes { data, uid, gid, .. } => CopySource::Bytes { data, mode, uid, gid },
+            path => path,
+        }
+    }
+
+    /// Sets the user and group id owning the file.
+    ///
+    /// Only applies to [`CopySource::Bytes`], see [`with_preserved_owner`](Self::with_preserved_owner) for paths.
+    pub fn with_owner(self, uid: u32, gid: u32) -> Self {
+        match self {
+            CopySource::Bytes { data, mode, .. } => CopySource::Bytes { data, mode, uid, gid },
+            path => path,
+        }
+    }
+}
+
+impl From<PathBuf> for CopySource {
+    fn from(path: PathBuf) -> Self {
+        CopySource::path(path)
+    }
+}
+
+impl From<&Path> for CopySource {
+    fn from(path: &Path) -> Self {
+        CopySource::path(path)
+    }
+}
+
+impl From<Vec<u8>> for CopySource {
+    fn from(data: Vec<u8>) -> Self {
+        CopySource::bytes(data)
+    }
+}
+
+impl From<&[u8]> for CopySource {
+    fn from(data: &[u8]) -> Self {
+        CopySource::bytes(data)
+    }
+}
+
+pub struct CpCommand;
+
+impl CpCommand {
+    /// Copies the source to the given absolute path inside the container, which does not need to be running.
+    pub async fn copy_to_container(
+        container_id: &str,
+        source: &CopySource,
+        container_path: &str,
+    ) -> Result<(), DockerError> {
+        match source {
+            CopySource::Path { path, preserve_owner } => {
+                let mut command = Command::new("docker");
+                command.arg("cp");
+                if *preserve_owner {
+                    command.arg("-a");
+                }
+                command.arg(path).arg(format!("{}:{}", container_id, container_path));
+                execute(&mut command).await?;
+            }
+            CopySource::Bytes { data, mode, uid, gid } => {
+                // `docker cp` reads a tar archive from stdin and extracts it into an existing directory.
+                let (directory, file_name) = match container_path.rfind('/') {
+                    Some(index) if index + 1 < container_path.len() => {
+                        (&container_path[..=index], &container_path[index + 1..])
+                    }
+                    _ => {
+                        return Err(DockerError::InvalidArgument(format!(
+                            "{} is not an absolute file path",
+                            container_path
+                        )))
+                    }
+                };
+                if file_name.len() > tar::MAX_NAME_LENGTH {
+                    return Err(DockerError::InvalidArgument(format!(
+                        "file name {} is longer than {} bytes",
+                        file_name,
+                        tar::MAX_NAME_LENGTH
+                    )));
+                }
+                if data.len() as u64 > tar::MAX_FILE_SIZE {
+                    return Err(DockerError::InvalidArgument(format!(
+                        "file of {} bytes is larger than the {} bytes a single copy supports",
+                        data.len(),
+                        tar::MAX_FILE_SIZE
+                    )));
+                }
+                if *uid > tar::MAX_ID || *gid > tar::MAX_ID {
+                    return Err(DockerError::InvalidArgument(format!(
+                        "owner {}:{} exceeds the largest supported id {}",
+                        uid,
+                        gid,
+                        tar::MAX_ID
+                    )));
+                }
+                let archive = tar::single_file_archive(file_name, data, *mode, *uid, *gid);
+
+                let mut command = Command::new("docker");
+                command
+                    .arg("cp")
+                    .arg("-")
+                    .arg(format!("{}:{}", container_id, directory))
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped())
+                    .kill_on_drop(true);
+                log::debug!("Executing command: {:?}", command);
+                let mut child = command.spawn()?;
+                let mut stdin = child.stdin.take().expect("failed to unwrap stdin docker cp command");
+                stdin.write_all(&archive).await?;
+                drop(stdin);
+                let output = child.wait_with_output().await?;
+                if !output.status.success() {
+                    return Err(DockerError::CommandFailed {
+                        command: format!("{:?}", command),
+                        code: output.status.code(),
+                        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a tar archive of the file or directory at the given path inside the container.
+    pub async fn copy_archive_from_container(container_id: &str, container_path: &str) -> Result<Vec<u8>, DockerError> {
+        let mut command = Command::new("docker");
+        command
+            .arg("cp")
+            .arg("-L") // Follow symbolic links in the source path
+            .arg(format!("{}:{}", container_id, container_path))
+            .arg("-");
+        let output = execute(&mut command).await?;
+        Ok(output.stdout)
+    }
+
+    /// Returns the contents of the file at the given path inside the container.
+    pub async fn copy_file_from_container(container_id: &str, container_path: &str) -> Result<Vec<u8>, DockerError> {
+        let archive = CpCommand::copy_archive_from_container(container_id, container_path).await?;
+        tar::first_file_contents(&archive)
+            .map_err(|reason| DockerError::UnexpectedOutput(format!("{}: {}", container_path, reason)))
+    }
+
+    /// Copies the file or directory at the given path inside the container to the host.
+    pub async fn copy_from_container_to_path(
+        container_id: &str,
+        container_path: &str,
+        host_path: &Path,
+    ) -> Result<(), DockerError> {
+        let mut command = Command::new("docker");
+        command
+            .arg("cp")
+            .arg("-L") // Follow symbolic links in the source path
+            .arg(format!("{}:{}", container_id, container_path))
+            .arg(host_path);
+        execute(&mut command).await?;
+        Ok(())
+    }
+}
+
+/// Spawns the given command with piped output streams, killing it when the returned process is dropped.
+fn spawn_attached(mut command: Command) -> Result<ExecProcess, DockerError> {
+    command.stdout(Stdio::piped()).stderr(Stdio::piped()).kill_on_drop(true);
+    log::debug!("Executing command: {:?}", command);
+    let child = command.spawn()?;
+    Ok(ExecProcess { child })
+}
+
+/// Runs the given command to completion and fails if it exits with a non-zero status.
+pub(crate) async fn execute(command: &mut Command) -> Result<StdOutput, DockerError> {
+    log::debug!("Executing command: {:?}", command);
+    let output = command.stdin(Stdio::null()).output().await?;
+    if output.status.success() {
+        Ok(output)
+    } else {
+        Err(DockerError::CommandFailed {
+            command: format!("{:?}", command),
+            code: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
 }
@@ -1,5 +1,15 @@
-use crate::{Image, InspectCommand, LogsCommand, RmCommand, RunCommand, StopCommand, WaitError};
-use std::time::{Duration, Instant};
+use crate::{
+    host, AttachCommand, ContainerInfo, CopySource, CpCommand, CreateCommand, DockerError, EndpointSettings,
+    ExecCommand, ExecProcess, ExecRequest, ExitStatus, Image, ImagePuller, InspectCommand, KillCommand, LogsCommand,
+    NetworkCommand, Output, PauseCommand, Ports, RestartCommand, RmCommand, RunCommand, StartCommand, StopCommand,
+    WaitCommand, WaitError,
+};
+use std::{
+    net::{IpAddr, SocketAddr},
+    path::Path,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 use tokio::runtime::Runtime;
 
 const ONE_SECOND: Duration = Duration::from_secs(1);
@@ -10,7 +20,7 @@ where
     I: Image,
 {
     id: String,
-    start_time: std::time::Instant,
+    start_time: Mutex<Instant>,
     image: I,
 }
 
@@ -18,16 +28,43 @@ impl<I> DockerContainer<I>
 where
     I: Image,
 {
-    pub async fn new(image: I) -> Result<Self, WaitError> {
-        let id = RunCommand::create_container(&image).await;
-        let start_time = std::time::Instant::now();
-        log::trace!("Registering starting of container {} at {:?}", id, start_time);
-        let container = DockerContainer { id, start_time, image };
-        wait_at_least_one_second_after_container_was_started(&container.id, &container.start_time).await;
-        LogsCommand::wait_until_ready(&container.id, container.image().wait_for()).await?;
+    /// Pulls the image according to [`Image::pull_policy`], runs it and waits until it is ready.
+    ///
+    /// The time spent pulling does not count against the [`Image::wait_for`] deadline.
+    pub async fn new(image: I) -> Result<Self, DockerError> {
+        pull_image(&image).await?;
+        let id = RunCommand::create_container(&image).await?;
+        let container = DockerContainer {
+            id,
+            start_time: Mutex::new(Instant::now()),
+            image,
+        };
+        container.register_start();
+        container.wait_until_ready().await?;
         Ok(container)
     }
 
+    /// Creates the docker container without starting it.
+    ///
+    /// This allows preparing the container, e.g. through [`copy_to`](Self::copy_to), before it is started through
+    /// [`start`](Self::start) and awaited through [`wait_until_ready`](Self::wait_until_ready).
+    pub async fn create(image: I) -> Result<Self, DockerError> {
+        pull_image(&image).await?;
+        let id = CreateCommand::create_container(&image).await?;
+        log::debug!("Created docker container {}", id);
+        Ok(DockerContainer {
+            id,
+            start_time: Mutex::new(Instant::now()),
+            image,
+        })
+    }
+
+    /// Waits until the docker container is ready according to [`Image::wait_for`].
+    pub async fn wait_until_ready(&self) -> Result<(), WaitError> {
+        wait_at_least_one_second_after_container_was_started(&self.id, &self.start_time()).await;
+        LogsCommand::wait_until_ready(&self.id, self.image.wait_for()).await
+    }
+
     pub fn id(&self) -> String {
         self.id.clone()
     }
@@ -37,17 +74,17 @@ where
     }
 
     pub async fn print_stdout(&self) {
-        wait_at_least_one_second_after_container_was_started(&self.id, &self.start_time).await;
+        wait_at_least_one_second_after_container_was_started(&self.id, &self.start_time()).await;
         LogsCommand::print_stdout(&self.id).await;
     }
 
     pub async fn print_stderr(&self) {
-        wait_at_least_one_second_after_container_was_started(&self.id, &self.start_time).await;
+        wait_at_least_one_second_after_container_was_started(&self.id, &self.start_time()).await;
         LogsCommand::print_stderr(&self.id).await;
     }
 
     async fn run_background_logs(&self, stdout: bool, stderr: bool) {
-        wait_at_least_one_second_after_container_was_started(&self.id, &self.start_time).await;
+        wait_at_least_one_second_after_container_was_started(&self.id, &self.start_time()).await;
         let id = self.id.clone();
         log::warn!("Starting new thread for background logs of container {}", self.id);
         std::thread::spawn(move || {
@@ -76,6 +113,22 @@ where
         self.run_background_logs(false, true).await;
     }
 
+    /// Returns the current configuration and state of this docker container as reported by `docker inspect`.
+    pub async fn inspect(&self) -> Result<ContainerInfo, DockerError> {
+        InspectCommand::inspect_container(&self.id).await
+    }
+
+    /// Returns the name of this docker container, including the unique suffix added to [`Image::container_name`].
+    pub async fn name(&self) -> Result<String, DockerError> {
+        let info = InspectCommand::inspect_container(&self.id).await?;
+        Ok(info.name.trim_start_matches('/').to_owned())
+    }
+
+    /// Returns all exposed ports of this docker container that are bound on the host.
+    pub async fn ports(&self) -> Ports {
+        InspectCommand::get_container_ports(&self.id).await
+    }
+
     /// Returns the mapped host port for an internal port of this docker container.
     ///
     /// This method does **not** magically expose the given port, it simply performs a mapping on
@@ -92,9 +145,194 @@ where
         resolved_port
     }
 
+    /// Starts this docker container again after it was stopped or killed.
+    ///
+    /// Unless the image binds fixed host ports, the container may be assigned new host ports, so
+    /// they have to be resolved again through [`get_host_port`](Self::get_host_port).
+    pub async fn start(&self) -> Result<(), DockerError> {
+        log::debug!("Starting docker container {}", self.id);
+        StartCommand::start_container(&self.id).await?;
+        self.register_start();
+        Ok(())
+    }
+
+    /// Restarts this docker container.
+    pub async fn restart(&self) -> Result<(), DockerError> {
+        log::debug!("Restarting docker container {}", self.id);
+        RestartCommand::restart_container(&self.id).await?;
+        self.register_start();
+        Ok(())
+    }
+
+    /// Suspends all processes of this docker container.
+    pub async fn pause(&self) -> Result<(), DockerError> {
+        log::debug!("Pausing docker container {}", self.id);
+        PauseCommand::pause_container(&self.id).await
+    }
+
+    /// Resumes all processes of this docker container after it was paused.
+    pub async fn unpause(&self) -> Result<(), DockerError> {
+        log::debug!("Unpausing docker container {}", self.id);
+        PauseCommand::unpause_container(&self.id).await
+    }
+
+    /// Sends the given signal (e.g. `SIGKILL`, `SIGTERM` or `9`) to the main process of this docker container.
+    pub async fn kill(&self, signal: &str) -> Result<(), DockerError> {
+        log::debug!("Sending {} to docker container {}", signal, self.id);
+        KillCommand::kill_container(&self.id, signal).await
+    }
+
+    /// Stops this docker container, killing it if it did not exit within the given timeout.
+    ///
+    /// The timeout has a granularity of one second.
+    pub async fn stop_with_timeout(&self, timeout: Duration) -> Result<(), DockerError> {
+        log::debug!("Stopping docker container {} with timeout {:?}", self.id, timeout);
+        StopCommand::stop_container_with_timeout(&self.id, timeout).await
+    }
+
+    /// Waits until this docker container exits and returns the exit status of its main process.
+    ///
+    /// Fails with [`DockerError::Timeout`] if the container is still running after `timeout`.
+    pub async fn wait_for_exit(&self, timeout: Duration) -> Result<ExitStatus, DockerError> {
+        log::debug!("Waiting for docker container {} to exit", self.id);
+        WaitCommand::wait_container(&self.id, timeout).await
+    }
+
+    /// Runs a command inside this docker container and returns its exit code and captured output.
+    pub async fn exec(&self, request: ExecRequest) -> Result<Output, DockerError> {
+        log::debug!("Executing {:?} in docker container {}", request.cmd, self.id);
+        ExecCommand::exec(&self.id, &request).await
+    }
+
+    /// Starts a command inside this docker container and returns a handle to stream its output while it runs.
+    pub fn exec_streaming(&self, request: ExecRequest) -> Result<ExecProcess, DockerError> {
+        log::debug!("Executing {:?} in docker container {}", request.cmd, self.id);
+        ExecCommand::spawn(&self.id, &request)
+    }
+
+    /// Starts a command inside this docker container with stdin kept open.
+    ///
+    /// Input is written through [`ExecProcess::take_stdin`]; dropping or [closing](ExecProcess::close_stdin) it
+    /// signals end of input while the output can still be read, e.g. to feed a schema into `psql`.
+    pub fn exec_interactive(&self, request: ExecRequest) -> Result<ExecProcess, DockerError> {
+        log::debug!(
+            "Executing {:?} interactively in docker container {}",
+            request.cmd,
+            self.id
+        );
+        ExecCommand::spawn_interactive(&self.id, &request)
+    }
+
+    /// Attaches to the main process of this docker container.
+    ///
+    /// Writing to stdin requires the image to keep it open, see [`Image::stdin_open`]. Closing stdin only ends the
+    /// input of the main process for containers obtained through [`create`](Self::create), docker keeps it open for
+    /// containers started detached through [`new`](Self::new).
+    pub fn attach(&self) -> Result<ExecProcess, DockerError> {
+        log::debug!("Attaching to docker container {}", self.id);
+        AttachCommand::attach(&self.id)
+    }
+
+    /// Copies a host file or directory, or in-memory file contents, to the given absolute path in this container.
+    ///
+    /// Works both on running containers and on containers obtained through [`create`](Self::create) that were not
+    /// started yet. The parent directory of the destination must already exist.
+    pub async fn copy_to<S: Into<CopySource>>(&self, source: S, container_path: &str) -> Result<(), DockerError> {
+        log::debug!("Copying to {} in docker container {}", container_path, self.id);
+        CpCommand::copy_to_container(&self.id, &source.into(), container_path).await
+    }
+
+    /// Returns the contents of the file at the given path in this container.
+    pub async fn copy_from(&self, container_path: &str) -> Result<Vec<u8>, DockerError> {
+        log::debug!("Copying {} from docker container {}", container_path, self.id);
+        CpCommand::copy_file_from_container(&self.id, container_path).await
+    }
+
+    /// Returns a tar archive of the file or directory at the given path in this container.
+    pub async fn copy_archive_from(&self, container_path: &str) -> Result<Vec<u8>, DockerError> {
+        log::debug!(
+            "Copying archive of {} from docker container {}",
+            container_path,
+            self.id
+        );
+        CpCommand::copy_archive_from_container(&self.id, container_path).await
+    }
+
+    /// Copies the file or directory at the given path in this container to the host.
+    ///
+    /// If `host_path` is an existing directory, the copied file or directory is placed inside of it.
+    pub async fn copy_from_to_path<P: AsRef<Path>>(
+        &self,
+        container_path: &str,
+        host_path: P,
+    ) -> Result<(), DockerError> {
+        log::debug!(
+            "Copying {} from docker container {} to {:?}",
+            container_path,
+            self.id,
+            host_path.as_ref()
+        );
+        CpCommand::copy_from_container_to_path(&self.id, container_path, host_path.as_ref()).await
+    }
+
+    fn register_start(&self) {
+        let start_time = Instant::now();
+        log::trace!("Registering starting of container {} at {:?}", self.id, start_time);
+        *self.start_time.lock().unwrap() = start_time;
+    }
+
+    fn start_time(&self) -> Instant {
+        *self.start_time.lock().unwrap()
+    }
+
+    /// Returns the address of the host on which the ports of this docker container are published.
+    ///
+    /// This is the host of a remote daemon configured through `DOCKER_HOST`, the default gateway when running
+    /// inside a container, and localhost otherwise.
+    pub fn host(&self) -> IpAddr {
+        host::docker_host()
+    }
+
+    /// Returns the address on which an internal port of this docker container can be reached.
+    ///
+    /// Like [`get_host_port`](Self::get_host_port) this only resolves ports that are already published.
+    pub async fn get_host_address(&self, internal_port: u16) -> Option<SocketAddr> {
+        let port = self.get_host_port(internal_port).await?;
+        Some(SocketAddr::new(self.host(), port))
+    }
+
+    /// Returns the IP address of this docker container on the given network.
+    ///
+    /// Containers connected to the same user-defined network can reach each other on this address. Returns `None` if
+    /// the container is not connected to the network or not running.
+    pub async fn ip_address(&self, network: &str) -> Result<Option<IpAddr>, DockerError> {
+        let info = InspectCommand::inspect_container(&self.id).await?;
+        let ip = info.networks().get(network).and_then(EndpointSettings::ip);
+        if ip.is_none() {
+            log::warn!(
+                "Unable to resolve IP address of container {} on network {}",
+                self.id,
+                network
+            );
+        }
+        Ok(ip)
+    }
+
+    /// Connects this docker container to a network, where other containers can reach it through the given aliases.
+    pub async fn connect_to_network(&self, network: &str, aliases: &[&str]) -> Result<(), DockerError> {
+        log::debug!("Connecting docker container {} to network {}", self.id, network);
+        NetworkCommand::connect_container(network, &self.id, aliases).await
+    }
+
+    /// Disconnects this docker container from a network.
+    pub async fn disconnect_from_network(&self, network: &str) -> Result<(), DockerError> {
+        log::debug!("Disconnecting docker container {} from network {}", self.id, network);
+        NetworkCommand::disconnect_container(network, &self.id).await
+    }
+
     fn stop(&self) {
         log::debug!("Stopping docker container {}", self.id);
-        StopCommand::stop_container(&self.id);
+        StopCommand::stop_container(&self.id, self.image.stop_timeout());
     }
 
     fn rm(&self) {
@@ -103,11 +341,30 @@ where
     }
 }
 
+/// Runs the image to completion and returns its exit code together with everything it wrote to stdout and stderr.
+///
+/// The container is not detached and is removed as soon as it exits, which makes this suitable for throwaway tool
+/// containers (e.g. `psql` or `kcat`) pointed at services started through [`DockerContainer`].
+pub async fn run_once<I: Image>(image: &I) -> Result<Output, DockerError> {
+    log::debug!("Running image {} to completion", image.descriptor());
+    pull_image(image).await?;
+    RunCommand::run_once(image).await
+}
+
+async fn pull_image<I: Image>(image: &I) -> Result<(), DockerError> {
+    let mut puller = ImagePuller::new(image.pull_policy());
+    if let Some(platform) = image.platform() {
+        puller = puller.with_platform(platform);
+    }
+    puller.apply(&image.descriptor()).await?;
+    Ok(())
+}
+
 async fn wait_at_least_one_second_after_container_was_started(container_id: &str, start_time: &Instant) {
     let duration = Instant::now() - *start_time;
     log::trace!("Time since container {} was started: {:?}", container_id, duration);
     if duration < ONE_SECOND {
-        tokio::time::delay_for(ONE_SECOND.checked_sub(duration).unwrap_or_else(|| ZERO)).await;
+        tokio::time::delay_for(ONE_SECOND.checked_sub(duration).unwrap_or(ZERO)).await;
     }
 }
 
@@ -120,13 +377,16 @@ where
     I: Image,
 {
     fn drop(&mut self) {
-        let keep_container = std::env::var("KEEP_CONTAINERS")
-            .ok()
-            .and_then(|var| var.parse().ok())
-            .unwrap_or(false);
-        match keep_container {
+        match keep_containers() {
             true => self.stop(),
             false => self.rm(),
         }
     }
 }
+
+pub(crate) fn keep_containers() -> bool {
+    std::env::var("KEEP_CONTAINERS")
+        .ok()
+        .and_then(|var| var.parse().ok())
+        .unwrap_or(false)
+}
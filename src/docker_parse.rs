@@ -1,63 +1,384 @@
-use crate::Ports as DockerPorts;
-use serde::Deserialize;
-use std::collections::HashMap;
+use crate::{ExposedPort, HostBinding, Ports as DockerPorts, Protocol};
+use serde::{Deserialize, Deserializer};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr},
+};
 
-#[derive(Deserialize, Debug)]
-struct NetworkSettings {
-    #[serde(rename = "Ports")]
-    ports: Ports,
+/// Docker reports empty collections as `null`, which is deserialized into the default value.
+fn nullable<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Option::<T>::deserialize(deserializer).map(Option::unwrap_or_default)
 }
 
-#[derive(Deserialize, Debug)]
-struct PortMapping {
-    #[serde(rename = "HostIp")]
-    ip: String,
-    #[serde(rename = "HostPort")]
-    port: String,
-}
-
-#[derive(Deserialize, Debug)]
+/// The output of `docker inspect` for a container.
+///
+/// Only a subset of the fields is modelled, unknown fields are ignored and missing fields take their default value.
+/// Timestamps are kept in the RFC 3339 format docker prints them in.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
 pub struct ContainerInfo {
     #[serde(rename = "Id")]
-    id: String,
+    pub id: String,
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Created")]
+    pub created: String,
+    /// The id of the image the container was created from.
+    #[serde(rename = "Image")]
+    pub image: String,
+    #[serde(rename = "RestartCount")]
+    pub restart_count: u32,
+    #[serde(rename = "State")]
+    pub state: ContainerState,
+    #[serde(rename = "Config")]
+    pub config: ContainerConfig,
+    #[serde(rename = "HostConfig")]
+    pub host_config: ContainerHostConfig,
+    #[serde(rename = "Mounts", deserialize_with = "nullable")]
+    pub mounts: Vec<ContainerMount>,
     #[serde(rename = "NetworkSettings")]
-    network_settings: NetworkSettings,
+    pub network_settings: ContainerNetworkSettings,
 }
 
 impl ContainerInfo {
-    pub fn get_ports(self) -> DockerPorts {
-        self.network_settings.ports.into_ports()
+    /// Returns the settings of the container on each network it is connected to, keyed by network name.
+    pub fn networks(&self) -> &HashMap<String, EndpointSettings> {
+        &self.network_settings.networks
+    }
+
+    pub fn get_ports(&self) -> DockerPorts {
+        into_ports(&self.network_settings.ports)
     }
 }
 
-#[derive(Deserialize, Debug)]
-struct Ports(HashMap<String, Option<Vec<PortMapping>>>);
+/// The runtime state of a container.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ContainerState {
+    /// One of `created`, `running`, `paused`, `restarting`, `removing`, `exited` or `dead`.
+    #[serde(rename = "Status")]
+    pub status: String,
+    #[serde(rename = "Running")]
+    pub running: bool,
+    #[serde(rename = "Paused")]
+    pub paused: bool,
+    #[serde(rename = "Restarting")]
+    pub restarting: bool,
+    #[serde(rename = "OOMKilled")]
+    pub oom_killed: bool,
+    #[serde(rename = "Dead")]
+    pub dead: bool,
+    /// The process id of the main process on the host, `0` while the container is not running.
+    #[serde(rename = "Pid")]
+    pub pid: u32,
+    #[serde(rename = "ExitCode")]
+    pub exit_code: i32,
+    #[serde(rename = "Error")]
+    pub error: String,
+    #[serde(rename = "StartedAt")]
+    pub started_at: String,
+    #[serde(rename = "FinishedAt")]
+    pub finished_at: String,
+    /// Only present if the image or container defines a health check.
+    #[serde(rename = "Health")]
+    pub health: Option<ContainerHealth>,
+}
 
-impl Ports {
-    pub fn into_ports(self) -> DockerPorts {
-        let mut ports = DockerPorts::default();
+/// The results of the health check of a container.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ContainerHealth {
+    /// One of `starting`, `healthy` or `unhealthy`.
+    #[serde(rename = "Status")]
+    pub status: String,
+    #[serde(rename = "FailingStreak")]
+    pub failing_streak: u32,
+    #[serde(rename = "Log", deserialize_with = "nullable")]
+    pub log: Vec<HealthCheckResult>,
+}
 
-        for (internal, external) in self.0 {
-            let external = match external.and_then(|mut m| m.pop()).map(|m| m.port) {
-                Some(port) => port,
-                None => {
-                    log::debug!("Port {} is not mapped to host machine, skipping.", internal);
-                    continue;
-                }
-            };
+/// The result of a single run of a health check.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct HealthCheckResult {
+    #[serde(rename = "Start")]
+    pub start: String,
+    #[serde(rename = "End")]
+    pub end: String,
+    #[serde(rename = "ExitCode")]
+    pub exit_code: i32,
+    #[serde(rename = "Output")]
+    pub output: String,
+}
 
-            let port = internal.split('/').next().unwrap();
+/// The configuration of a container that does not depend on the host.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ContainerConfig {
+    #[serde(rename = "Hostname")]
+    pub hostname: String,
+    #[serde(rename = "User")]
+    pub user: String,
+    /// Environment variables in `KEY=value` form.
+    #[serde(rename = "Env", deserialize_with = "nullable")]
+    pub env: Vec<String>,
+    #[serde(rename = "Cmd", deserialize_with = "nullable")]
+    pub cmd: Vec<String>,
+    #[serde(rename = "Entrypoint", deserialize_with = "nullable")]
+    pub entrypoint: Vec<String>,
+    /// The image descriptor the container was created with.
+    #[serde(rename = "Image")]
+    pub image: String,
+    #[serde(rename = "WorkingDir")]
+    pub working_dir: String,
+    #[serde(rename = "Labels", deserialize_with = "nullable")]
+    pub labels: HashMap<String, String>,
+    #[serde(rename = "StopSignal")]
+    pub stop_signal: Option<String>,
+    #[serde(rename = "StopTimeout")]
+    pub stop_timeout: Option<u64>,
+}
 
-            let internal = Self::parse_port(port);
-            let external = Self::parse_port(&external);
+impl ContainerConfig {
+    /// Returns the environment variables as key value pairs.
+    pub fn env_vars(&self) -> HashMap<String, String> {
+        self.env
+            .iter()
+            .map(|var| match var.split_once('=') {
+                Some((key, value)) => (key.to_owned(), value.to_owned()),
+                None => (var.clone(), String::new()),
+            })
+            .collect()
+    }
+}
+
+/// The configuration of a container that depends on the host.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ContainerHostConfig {
+    #[serde(rename = "NetworkMode")]
+    pub network_mode: String,
+    #[serde(rename = "RestartPolicy")]
+    pub restart_policy: RestartPolicyConfig,
+    #[serde(rename = "AutoRemove")]
+    pub auto_remove: bool,
+    #[serde(rename = "Privileged")]
+    pub privileged: bool,
+    #[serde(rename = "ReadonlyRootfs")]
+    pub readonly_rootfs: bool,
+    #[serde(rename = "PublishAllPorts")]
+    pub publish_all_ports: bool,
+    #[serde(rename = "CapAdd", deserialize_with = "nullable")]
+    pub cap_add: Vec<String>,
+    #[serde(rename = "CapDrop", deserialize_with = "nullable")]
+    pub cap_drop: Vec<String>,
+    #[serde(rename = "ExtraHosts", deserialize_with = "nullable")]
+    pub extra_hosts: Vec<String>,
+    /// The memory limit in bytes, `0` if unlimited.
+    #[serde(rename = "Memory")]
+    pub memory: i64,
+    #[serde(rename = "NanoCpus")]
+    pub nano_cpus: i64,
+    #[serde(rename = "ShmSize")]
+    pub shm_size: i64,
+    /// The maximum number of processes, `None` if unlimited.
+    #[serde(rename = "PidsLimit")]
+    pub pids_limit: Option<i64>,
+}
+
+/// The restart policy of a container as reported by `docker inspect`.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct RestartPolicyConfig {
+    /// One of `no`, `always`, `on-failure` or `unless-stopped`, empty if not set.
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "MaximumRetryCount")]
+    pub maximum_retry_count: u32,
+}
+
+/// A mount of a container as reported by `docker inspect`.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ContainerMount {
+    /// One of `bind`, `volume` or `tmpfs`.
+    #[serde(rename = "Type")]
+    pub mount_type: String,
+    /// The name of the volume, only set for volumes.
+    #[serde(rename = "Name")]
+    pub name: Option<String>,
+    #[serde(rename = "Source")]
+    pub source: String,
+    #[serde(rename = "Destination")]
+    pub destination: String,
+    #[serde(rename = "Driver")]
+    pub driver: Option<String>,
+    #[serde(rename = "RW")]
+    pub rw: bool,
+    #[serde(rename = "Propagation")]
+    pub propagation: String,
+}
+
+/// The network configuration of a container.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ContainerNetworkSettings {
+    /// The host bindings of each exposed port, keyed by `port/protocol`. See [`ContainerInfo::get_ports`].
+    #[serde(rename = "Ports", deserialize_with = "nullable")]
+    pub ports: HashMap<String, Option<Vec<PortMapping>>>,
+    #[serde(rename = "Networks", deserialize_with = "nullable")]
+    pub networks: HashMap<String, EndpointSettings>,
+}
+
+/// A host binding of an exposed port as reported by `docker inspect`.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct PortMapping {
+    #[serde(rename = "HostIp")]
+    pub host_ip: String,
+    #[serde(rename = "HostPort")]
+    pub host_port: String,
+}
 
-            ports.add_mapping(internal, external);
+/// The settings of a container on one of the networks it is connected to.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct EndpointSettings {
+    #[serde(rename = "NetworkID")]
+    pub network_id: String,
+    #[serde(rename = "IPAddress")]
+    pub ip_address: String,
+    #[serde(rename = "IPPrefixLen")]
+    pub ip_prefix_len: u8,
+    #[serde(rename = "Gateway")]
+    pub gateway: String,
+    #[serde(rename = "GlobalIPv6Address")]
+    pub global_ipv6_address: String,
+    #[serde(rename = "MacAddress")]
+    pub mac_address: String,
+    #[serde(rename = "Aliases")]
+    pub aliases: Option<Vec<String>>,
+}
+
+impl EndpointSettings {
+    /// Returns the IPv4 address of the container on the network, `None` while the container is not running.
+    pub fn ip(&self) -> Option<IpAddr> {
+        self.ip_address.parse().ok()
+    }
+
+    /// Returns the IPv6 address of the container on the network, if IPv6 is enabled on the network.
+    pub fn ipv6(&self) -> Option<IpAddr> {
+        self.global_ipv6_address.parse().ok()
+    }
+
+    pub fn gateway(&self) -> Option<IpAddr> {
+        self.gateway.parse().ok()
+    }
+
+    /// Returns the DNS names under which other containers on the network can reach the container.
+    pub fn aliases(&self) -> &[String] {
+        self.aliases.as_deref().unwrap_or(&[])
+    }
+}
+
+/// The output of `docker inspect` for an image.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ImageInfo {
+    #[serde(rename = "Id")]
+    pub id: String,
+    #[serde(rename = "RepoTags", deserialize_with = "nullable")]
+    pub repo_tags: Vec<String>,
+    #[serde(rename = "RepoDigests", deserialize_with = "nullable")]
+    pub repo_digests: Vec<String>,
+    #[serde(rename = "Os")]
+    pub os: String,
+    #[serde(rename = "Architecture")]
+    pub architecture: String,
+    /// The CPU variant, e.g. `v7` for `arm`, only reported for some architectures.
+    #[serde(rename = "Variant")]
+    pub variant: Option<String>,
+    /// The size of the image in bytes.
+    #[serde(rename = "Size")]
+    pub size: u64,
+}
+
+impl ImageInfo {
+    /// Returns the platform of the image in the `os/architecture[/variant]` form docker uses for `--platform`.
+    pub fn platform(&self) -> String {
+        match &self.variant {
+            Some(variant) if !variant.is_empty() => format!("{}/{}/{}", self.os, self.architecture, variant),
+            _ => format!("{}/{}", self.os, self.architecture),
         }
-        ports
     }
 
-    fn parse_port(port: &str) -> u16 {
-        port.parse()
-            .unwrap_or_else(|e| panic!("Failed to parse {} as u16 because {}", port, e))
+    /// Checks whether the image can run on the given `os[/architecture[/variant]]` platform.
+    ///
+    /// Components missing from the platform match any value, a variant is only compared if the image reports one.
+    // `Option::is_none_or` needs a newer compiler than the rest of the crate.
+    #[allow(clippy::unnecessary_map_or)]
+    pub fn matches_platform(&self, platform: &str) -> bool {
+        let mut parts = platform.split('/');
+        let os = parts.next().unwrap_or_default();
+        let architecture = parts.next();
+        let variant = parts.next();
+        os.eq_ignore_ascii_case(&self.os)
+            && architecture.map_or(true, |architecture| {
+                architecture.eq_ignore_ascii_case(&self.architecture)
+            })
+            && match (variant, self.variant.as_deref()) {
+                (Some(requested), Some(actual)) if !actual.is_empty() => requested.eq_ignore_ascii_case(actual),
+                _ => true,
+            }
+    }
+}
+
+fn into_ports(mapping: &HashMap<String, Option<Vec<PortMapping>>>) -> DockerPorts {
+    let mut ports = DockerPorts::default();
+
+    for (internal, external) in mapping {
+        let mappings = match external {
+            Some(mappings) if !mappings.is_empty() => mappings,
+            _ => {
+                log::debug!("Port {} is not mapped to host machine, skipping.", internal);
+                continue;
+            }
+        };
+
+        let mut parts = internal.split('/');
+        let port = parse_port(parts.next().unwrap());
+        let protocol = match parts.next().map(Protocol::parse) {
+            None => Protocol::Tcp,
+            Some(Some(protocol)) => protocol,
+            Some(None) => {
+                log::warn!("Port {} uses an unknown protocol, skipping.", internal);
+                continue;
+            }
+        };
+        let internal = ExposedPort { port, protocol };
+
+        for mapping in mappings {
+            // Older docker versions report an empty host IP for bindings on all interfaces.
+            let ip = if mapping.host_ip.is_empty() {
+                IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+            } else {
+                mapping
+                    .host_ip
+                    .parse()
+                    .unwrap_or_else(|e| panic!("Failed to parse {} as IP address because {}", mapping.host_ip, e))
+            };
+            let port = parse_port(&mapping.host_port);
+            ports.add_binding(internal, HostBinding { ip, port });
+        }
     }
+    ports
+}
+
+fn parse_port(port: &str) -> u16 {
+    port.parse()
+        .unwrap_or_else(|e| panic!("Failed to parse {} as u16 because {}", port, e))
 }
@@ -24,3 +24,85 @@ impl Display for WaitError {
         }
     }
 }
+
+/// Defines error cases when running a docker command.
+#[derive(Debug)]
+pub enum DockerError {
+    /// The docker command exited with a non-zero status.
+    CommandFailed {
+        command: String,
+        code: Option<i32>,
+        stderr: String,
+    },
+    /// The docker command could not be built from the given arguments.
+    InvalidArgument(String),
+    /// The docker command printed output that could not be understood.
+    UnexpectedOutput(String),
+    /// The docker command did not finish within the given duration.
+    Timeout,
+    /// The image is not present locally and the pull policy forbids pulling it.
+    ImageNotFound(String),
+    /// Pulling some of the images failed, with the error for each of their descriptors.
+    PullFailed(Vec<(String, DockerError)>),
+    /// The local image was built for a different platform than the one requested.
+    PlatformMismatch {
+        image: String,
+        requested: String,
+        actual: String,
+    },
+    Wait(WaitError),
+    Io(io::Error),
+}
+
+impl From<io::Error> for DockerError {
+    fn from(e: io::Error) -> Self {
+        DockerError::Io(e)
+    }
+}
+
+impl From<WaitError> for DockerError {
+    fn from(e: WaitError) -> Self {
+        DockerError::Wait(e)
+    }
+}
+
+impl Display for DockerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DockerError::CommandFailed { command, code, stderr } => f.write_fmt(format_args!(
+                "dockerust > command `{}` failed with code {:?}: {}",
+                command,
+                code,
+                stderr.trim()
+            )),
+            DockerError::InvalidArgument(reason) => {
+                f.write_fmt(format_args!("dockerust > invalid argument: {}", reason))
+            }
+            DockerError::UnexpectedOutput(output) => {
+                f.write_fmt(format_args!("dockerust > unexpected command output: {}", output))
+            }
+            DockerError::Timeout => f.write_fmt(format_args!("dockerust > command timed out")),
+            DockerError::ImageNotFound(image) => f.write_fmt(format_args!(
+                "dockerust > image {} is not present locally and must not be pulled",
+                image
+            )),
+            DockerError::PullFailed(failures) => {
+                f.write_fmt(format_args!("dockerust > failed to pull {} images", failures.len()))?;
+                for (image, err) in failures {
+                    f.write_fmt(format_args!("\n  {}: {}", image, err))?;
+                }
+                Ok(())
+            }
+            DockerError::PlatformMismatch {
+                image,
+                requested,
+                actual,
+            } => f.write_fmt(format_args!(
+                "dockerust > image {} is available for platform {} but {} was requested",
+                image, actual, requested
+            )),
+            DockerError::Wait(err) => err.fmt(f),
+            DockerError::Io(err) => f.write_fmt(format_args!("dockerust > tokio-io error: {}", err)),
+        }
+    }
+}
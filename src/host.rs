@@ -0,0 +1,127 @@
+use std::{
+    fs,
+    net::{IpAddr, Ipv4Addr, ToSocketAddrs},
+    path::Path,
+};
+
+/// Returns the address on which ports published by the docker daemon can be reached from this process.
+///
+/// - A remote daemon configured through a `tcp://`, `http(s)://` or `ssh://` `DOCKER_HOST` publishes ports on its
+///   own host.
+/// - When running inside a container (docker-in-docker or sibling containers), published ports are reachable
+///   through the default gateway of the container.
+/// - Otherwise the daemon runs on this machine and ports are published on localhost.
+pub(crate) fn docker_host() -> IpAddr {
+    if let Some(host) = std::env::var("DOCKER_HOST").ok().and_then(|url| remote_host(&url)) {
+        match resolve(&host) {
+            Some(ip) => {
+                log::trace!("Using docker host {} ({}) from DOCKER_HOST", host, ip);
+                return ip;
+            }
+            None => log::warn!("Unable to resolve docker host {} from DOCKER_HOST", host),
+        }
+    }
+    if Path::new("/.dockerenv").exists() {
+        match fs::read_to_string("/proc/net/route")
+            .ok()
+            .and_then(|routes| default_gateway(&routes))
+        {
+            Some(gateway) => {
+                log::trace!(
+                    "Running inside a container, using default gateway {} as docker host",
+                    gateway
+                );
+                return IpAddr::V4(gateway);
+            }
+            None => log::warn!("Running inside a container but unable to determine the default gateway"),
+        }
+    }
+    IpAddr::V4(Ipv4Addr::LOCALHOST)
+}
+
+/// Extracts the host name of a `DOCKER_HOST` URL pointing at a remote daemon.
+fn remote_host(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    match scheme {
+        "tcp" | "http" | "https" | "ssh" => {}
+        _ => return None,
+    }
+    let authority = rest.split('/').next()?;
+    let authority = authority.rsplit('@').next()?;
+    let host = match authority.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next()?,
+        None => authority.split(':').next()?,
+    };
+    if host.is_empty() {
+        None
+    } else {
+        Some(host.to_owned())
+    }
+}
+
+fn resolve(host: &str) -> Option<IpAddr> {
+    if let Ok(ip) = host.parse() {
+        return Some(ip);
+    }
+    (host, 0)
+        .to_socket_addrs()
+        .ok()?
+        .map(|address| address.ip())
+        .min_by_key(IpAddr::is_ipv6)
+}
+
+/// Finds the gateway of the default route in the contents of `/proc/net/route`.
+fn default_gateway(routes: &str) -> Option<Ipv4Addr> {
+    routes.lines().skip(1).find_map(|line| {
+        let fields: Vec<_> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            [_, "00000000", gateway, ..] => {
+                // The kernel prints the address bytes as a hexadecimal number in host byte order.
+                let gateway = u32::from_str_radix(gateway, 16).ok()?;
+                Some(Ipv4Addr::from(gateway.to_ne_bytes()))
+            }
+            _ => None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remote_host_of_docker_host_urls() {
+        assert_eq!(remote_host("tcp://[::1]:2376"), Some("::1".to_owned()));
+        assert_eq!(remote_host("ssh://user@host:22"), Some("host".to_owned()));
+        assert_eq!(
+            remote_host("https://docker.example.com:2376/"),
+            Some("docker.example.com".to_owned())
+        );
+        assert_eq!(remote_host("tcp://10.0.0.5"), Some("10.0.0.5".to_owned()));
+        assert_eq!(remote_host("unix:///var/run/docker.sock"), None);
+        assert_eq!(remote_host("npipe:////./pipe/docker_engine"), None);
+        assert_eq!(remote_host("tcp://:2376"), None);
+        assert_eq!(remote_host("/var/run/docker.sock"), None);
+    }
+
+    #[test]
+    #[cfg(target_endian = "little")]
+    fn default_gateway_of_proc_net_route() {
+        let routes = "\
+Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+eth0\t0011A8C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\t0\t0\t0
+eth0\t00000000\t0100A8C0\t0003\t0\t0\t0\t00000000\t0\t0\t0
+";
+        assert_eq!(default_gateway(routes), Some(Ipv4Addr::new(192, 168, 0, 1)));
+    }
+
+    #[test]
+    fn no_default_gateway_without_default_route() {
+        let routes = "\
+Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+eth0\t0011A8C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\t0\t0\t0
+";
+        assert_eq!(default_gateway(routes), None);
+        assert_eq!(default_gateway(""), None);
+    }
+}
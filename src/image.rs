@@ -1,4 +1,11 @@
-use std::collections::HashMap;
+use crate::{DockerError, ExposedPort, ImageRef, Mount, PortBinding, PullPolicy, ResourceLimits, SecurityOptions};
+use std::{collections::HashMap, time::Duration};
+
+/// The magic `--add-host` address docker resolves to the address of the host running the daemon.
+pub const HOST_GATEWAY: &str = "host-gateway";
+
+/// The hostname under which containers reach the host through [`GenericImage::with_host_gateway`].
+pub const HOST_GATEWAY_HOSTNAME: &str = "host.docker.internal";
 
 /// Represents a docker image.
 pub trait Image: Sized + Clone + Default {
@@ -8,8 +15,100 @@ pub trait Image: Sized + Clone + Default {
     }
     fn env_vars(&self) -> HashMap<String, String>;
     fn args(&self) -> Vec<String>;
-    fn mounts(&self) -> Vec<HashMap<String, String>>;
+    fn mounts(&self) -> Vec<Mount>;
     fn network(&self) -> Option<String>;
+    /// Name of the container, a unique suffix is appended so tests can run in parallel.
+    fn container_name(&self) -> Option<String> {
+        None
+    }
+    fn hostname(&self) -> Option<String> {
+        None
+    }
+    /// Labels of the container in addition to the ones identifying the hotto-docker session.
+    fn labels(&self) -> HashMap<String, String> {
+        HashMap::new()
+    }
+    /// DNS names under which other containers on [`network`](Self::network) reach this one.
+    fn network_aliases(&self) -> Vec<String> {
+        vec![]
+    }
+    /// Container ports to expose in addition to the ones the image declares through `EXPOSE`.
+    fn exposed_ports(&self) -> Vec<ExposedPort> {
+        vec![]
+    }
+    /// Container ports to publish on explicit host ports or interfaces.
+    fn port_bindings(&self) -> Vec<PortBinding> {
+        vec![]
+    }
+    /// Publishes all exposed ports on random host ports.
+    fn publish_all_ports(&self) -> bool {
+        true
+    }
+    /// Keeps stdin of the main process open, so it can be written to through [`DockerContainer::attach`].
+    ///
+    /// [`DockerContainer::attach`]: crate::DockerContainer::attach
+    fn stdin_open(&self) -> bool {
+        false
+    }
+    /// Overrides the entrypoint of the image, [`args`](Self::args) are passed to it.
+    ///
+    /// An empty string clears the entrypoint, so the first argument is run as the command.
+    fn entrypoint(&self) -> Option<String> {
+        None
+    }
+    /// Overrides the working directory of the image.
+    fn workdir(&self) -> Option<String> {
+        None
+    }
+    /// Overrides the user of the image, as `name`, `uid` or `uid:gid`.
+    fn user(&self) -> Option<String> {
+        None
+    }
+    /// Additional `/etc/hosts` entries, keyed by hostname, the address may be [`HOST_GATEWAY`].
+    fn extra_hosts(&self) -> HashMap<String, String> {
+        HashMap::new()
+    }
+    /// DNS servers used instead of the ones of the host.
+    fn dns(&self) -> Vec<String> {
+        vec![]
+    }
+    /// Domains searched when resolving unqualified hostnames.
+    fn dns_search(&self) -> Vec<String> {
+        vec![]
+    }
+    /// Defines whether the image is pulled before a container is run from it.
+    fn pull_policy(&self) -> PullPolicy {
+        PullPolicy::IfMissing
+    }
+    /// The platform to run the image on in `os/architecture[/variant]` form, e.g. `linux/amd64`.
+    fn platform(&self) -> Option<String> {
+        None
+    }
+    fn restart_policy(&self) -> Option<RestartPolicy> {
+        None
+    }
+    /// The signal sent to the main process to stop the container, e.g. `SIGINT`.
+    fn stop_signal(&self) -> Option<String> {
+        None
+    }
+    /// How long to wait for the main process to exit after the stop signal before killing it.
+    fn stop_timeout(&self) -> Option<Duration> {
+        None
+    }
+    fn resource_limits(&self) -> ResourceLimits {
+        ResourceLimits::default()
+    }
+    fn security_options(&self) -> SecurityOptions {
+        SecurityOptions::default()
+    }
+    /// Namespaced kernel parameters, e.g. `net.core.somaxconn`.
+    fn sysctls(&self) -> HashMap<String, String> {
+        HashMap::new()
+    }
+    /// Runs an init process as PID 1 that forwards signals and reaps zombie processes.
+    fn init(&self) -> bool {
+        false
+    }
     fn with_args(self, args: Vec<String>) -> Self;
 }
 
@@ -47,13 +146,63 @@ impl WaitFor {
     }
 }
 
+/// Defines whether docker restarts a container when it exits.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RestartPolicy {
+    No,
+    Always,
+    /// Restarts the container when it exits with a non-zero code, at most `max_retries` times if given.
+    OnFailure {
+        max_retries: Option<u32>,
+    },
+    UnlessStopped,
+}
+
+impl RestartPolicy {
+    /// Renders the policy as the value of a `--restart` argument.
+    pub fn to_arg(&self) -> String {
+        match self {
+            RestartPolicy::No => "no".to_owned(),
+            RestartPolicy::Always => "always".to_owned(),
+            RestartPolicy::OnFailure { max_retries: None } => "on-failure".to_owned(),
+            RestartPolicy::OnFailure {
+                max_retries: Some(max_retries),
+            } => format!("on-failure:{}", max_retries),
+            RestartPolicy::UnlessStopped => "unless-stopped".to_owned(),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct GenericImage {
     descriptor: String,
     env_vars: HashMap<String, String>,
     args: Vec<String>,
-    mounts: Vec<HashMap<String, String>>,
+    mounts: Vec<Mount>,
     network: Option<String>,
+    network_aliases: Vec<String>,
+    container_name: Option<String>,
+    hostname: Option<String>,
+    labels: HashMap<String, String>,
+    entrypoint: Option<String>,
+    workdir: Option<String>,
+    user: Option<String>,
+    extra_hosts: HashMap<String, String>,
+    dns: Vec<String>,
+    dns_search: Vec<String>,
+    pull_policy: PullPolicy,
+    platform: Option<String>,
+    restart_policy: Option<RestartPolicy>,
+    stop_signal: Option<String>,
+    stop_timeout: Option<Duration>,
+    resource_limits: ResourceLimits,
+    security_options: SecurityOptions,
+    sysctls: HashMap<String, String>,
+    init: bool,
+    exposed_ports: Vec<ExposedPort>,
+    port_bindings: Vec<PortBinding>,
+    publish_all_ports: bool,
+    stdin_open: bool,
     wait_for: WaitFor,
 }
 
@@ -65,21 +214,57 @@ impl Default for GenericImage {
             args: vec![],
             mounts: vec![],
             network: None,
+            network_aliases: vec![],
+            container_name: None,
+            hostname: None,
+            labels: HashMap::new(),
+            entrypoint: None,
+            workdir: None,
+            user: None,
+            extra_hosts: HashMap::new(),
+            dns: vec![],
+            dns_search: vec![],
+            pull_policy: PullPolicy::IfMissing,
+            platform: None,
+            restart_policy: None,
+            stop_signal: None,
+            stop_timeout: None,
+            resource_limits: ResourceLimits::default(),
+            security_options: SecurityOptions::default(),
+            sysctls: HashMap::new(),
+            init: false,
+            exposed_ports: vec![],
+            port_bindings: vec![],
+            publish_all_ports: true,
+            stdin_open: false,
             wait_for: WaitFor::Nothing,
         }
     }
 }
 
 impl GenericImage {
+    /// Creates an image from a descriptor like `redis:5-alpine`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the descriptor is not a valid [`ImageRef`], use [`try_new`](Self::try_new) to handle that case.
     pub fn new<S: Into<String>>(descriptor: S) -> GenericImage {
-        Self {
-            descriptor: descriptor.into(),
-            env_vars: HashMap::new(),
-            args: vec![],
-            mounts: vec![],
-            network: None,
-            wait_for: WaitFor::Nothing,
-        }
+        GenericImage::try_new(descriptor).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Creates an image from a descriptor, failing with [`DockerError::InvalidArgument`] if it is malformed.
+    pub fn try_new<S: Into<String>>(descriptor: S) -> Result<GenericImage, DockerError> {
+        let descriptor = descriptor.into();
+        ImageRef::parse(&descriptor)?;
+        Ok(Self {
+            descriptor,
+            ..Default::default()
+        })
+    }
+
+    /// Returns the parsed descriptor.
+    pub fn image_ref(&self) -> Result<ImageRef, DockerError> {
+        ImageRef::parse(&self.descriptor)
     }
 
     pub fn with_env_var<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
@@ -87,13 +272,140 @@ impl GenericImage {
         self
     }
 
-    pub fn with_mount(mut self, mount: HashMap<String, String>) -> Self {
-        self.mounts.push(mount);
+    pub fn with_mount<M: Into<Mount>>(mut self, mount: M) -> Self {
+        self.mounts.push(mount.into());
+        self
+    }
+
+    pub fn with_network<S: Into<String>>(mut self, network: S) -> Self {
+        self.network = Some(network.into());
+        self
+    }
+
+    pub fn with_network_alias<S: Into<String>>(mut self, alias: S) -> Self {
+        self.network_aliases.push(alias.into());
+        self
+    }
+
+    pub fn with_container_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.container_name = Some(name.into());
+        self
+    }
+
+    pub fn with_hostname<S: Into<String>>(mut self, hostname: S) -> Self {
+        self.hostname = Some(hostname.into());
+        self
+    }
+
+    pub fn with_label<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn with_entrypoint<S: Into<String>>(mut self, entrypoint: S) -> Self {
+        self.entrypoint = Some(entrypoint.into());
+        self
+    }
+
+    pub fn with_workdir<S: Into<String>>(mut self, workdir: S) -> Self {
+        self.workdir = Some(workdir.into());
+        self
+    }
+
+    pub fn with_user<S: Into<String>>(mut self, user: S) -> Self {
+        self.user = Some(user.into());
+        self
+    }
+
+    /// Adds an `/etc/hosts` entry, the address may be [`HOST_GATEWAY`] to resolve the hostname to the docker host.
+    pub fn with_extra_host<H: Into<String>, A: Into<String>>(mut self, hostname: H, address: A) -> Self {
+        self.extra_hosts.insert(hostname.into(), address.into());
+        self
+    }
+
+    /// Makes the host, e.g. a mock server started by the test, reachable as [`HOST_GATEWAY_HOSTNAME`].
+    ///
+    /// Services on the host must listen on an interface reachable from the docker bridge, not only on localhost.
+    pub fn with_host_gateway(self) -> Self {
+        self.with_extra_host(HOST_GATEWAY_HOSTNAME, HOST_GATEWAY)
+    }
+
+    pub fn with_dns<S: Into<String>>(mut self, server: S) -> Self {
+        self.dns.push(server.into());
+        self
+    }
+
+    pub fn with_dns_search<S: Into<String>>(mut self, domain: S) -> Self {
+        self.dns_search.push(domain.into());
+        self
+    }
+
+    pub fn with_pull_policy(mut self, pull_policy: PullPolicy) -> Self {
+        self.pull_policy = pull_policy;
+        self
+    }
+
+    /// Runs the image for the given platform, e.g. `linux/amd64` to emulate it on arm64 hosts.
+    pub fn with_platform<S: Into<String>>(mut self, platform: S) -> Self {
+        self.platform = Some(platform.into());
+        self
+    }
+
+    pub fn with_restart_policy(mut self, restart_policy: RestartPolicy) -> Self {
+        self.restart_policy = Some(restart_policy);
+        self
+    }
+
+    pub fn with_stop_signal<S: Into<String>>(mut self, signal: S) -> Self {
+        self.stop_signal = Some(signal.into());
+        self
+    }
+
+    /// Sets the grace period before the container is killed when it is stopped, rounded up to whole seconds.
+    pub fn with_stop_timeout(mut self, timeout: Duration) -> Self {
+        self.stop_timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_resource_limits(mut self, resource_limits: ResourceLimits) -> Self {
+        self.resource_limits = resource_limits;
+        self
+    }
+
+    pub fn with_security_options(mut self, security_options: SecurityOptions) -> Self {
+        self.security_options = security_options;
+        self
+    }
+
+    pub fn with_sysctl<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.sysctls.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn with_init(mut self) -> Self {
+        self.init = true;
+        self
+    }
+
+    pub fn with_exposed_port<P: Into<ExposedPort>>(mut self, port: P) -> Self {
+        self.exposed_ports.push(port.into());
+        self
+    }
+
+    pub fn with_port_binding(mut self, binding: PortBinding) -> Self {
+        self.port_bindings.push(binding);
         self
     }
 
-    pub fn with_network(mut self, network: String) -> Self {
-        self.network = Some(network);
+    /// Only publishes the ports given through [`with_port_binding`](Self::with_port_binding) instead of all exposed
+    /// ports.
+    pub fn without_publish_all_ports(mut self) -> Self {
+        self.publish_all_ports = false;
+        self
+    }
+
+    pub fn with_stdin_open(mut self) -> Self {
+        self.stdin_open = true;
         self
     }
 
@@ -120,7 +432,7 @@ impl Image for GenericImage {
         self.args.clone()
     }
 
-    fn mounts(&self) -> Vec<HashMap<String, String>> {
+    fn mounts(&self) -> Vec<Mount> {
         self.mounts.clone()
     }
 
@@ -128,6 +440,98 @@ impl Image for GenericImage {
         self.network.clone()
     }
 
+    fn network_aliases(&self) -> Vec<String> {
+        self.network_aliases.clone()
+    }
+
+    fn container_name(&self) -> Option<String> {
+        self.container_name.clone()
+    }
+
+    fn hostname(&self) -> Option<String> {
+        self.hostname.clone()
+    }
+
+    fn labels(&self) -> HashMap<String, String> {
+        self.labels.clone()
+    }
+
+    fn exposed_ports(&self) -> Vec<ExposedPort> {
+        self.exposed_ports.clone()
+    }
+
+    fn port_bindings(&self) -> Vec<PortBinding> {
+        self.port_bindings.clone()
+    }
+
+    fn publish_all_ports(&self) -> bool {
+        self.publish_all_ports
+    }
+
+    fn stdin_open(&self) -> bool {
+        self.stdin_open
+    }
+
+    fn entrypoint(&self) -> Option<String> {
+        self.entrypoint.clone()
+    }
+
+    fn workdir(&self) -> Option<String> {
+        self.workdir.clone()
+    }
+
+    fn user(&self) -> Option<String> {
+        self.user.clone()
+    }
+
+    fn extra_hosts(&self) -> HashMap<String, String> {
+        self.extra_hosts.clone()
+    }
+
+    fn dns(&self) -> Vec<String> {
+        self.dns.clone()
+    }
+
+    fn dns_search(&self) -> Vec<String> {
+        self.dns_search.clone()
+    }
+
+    fn pull_policy(&self) -> PullPolicy {
+        self.pull_policy
+    }
+
+    fn platform(&self) -> Option<String> {
+        self.platform.clone()
+    }
+
+    fn restart_policy(&self) -> Option<RestartPolicy> {
+        self.restart_policy
+    }
+
+    fn stop_signal(&self) -> Option<String> {
+        self.stop_signal.clone()
+    }
+
+    fn stop_timeout(&self) -> Option<Duration> {
+        self.stop_timeout
+    }
+
+    fn resource_limits(&self) -> ResourceLimits {
+        self.resource_limits.clone()
+    }
+
+    fn security_options(&self) -> SecurityOptions {
+        self.security_options.clone()
+    }
+
+    fn sysctls(&self) -> HashMap<String, String> {
+        self.sysctls.clone()
+    }
+
+    fn init(&self) -> bool {
+        self.init
+    }
+
     fn with_args(self, args: Vec<String>) -> Self {
         Self { args, ..self }
     }
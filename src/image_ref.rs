@@ -0,0 +1,263 @@
+use crate::DockerError;
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+/// The registry images without an explicit registry are pulled from.
+pub const DOCKER_HUB_REGISTRY: &str = "docker.io";
+
+/// The namespace of the official images on Docker Hub, e.g. `library/redis` for `redis`.
+pub const DOCKER_HUB_OFFICIAL_NAMESPACE: &str = "library";
+
+/// The tag used when a reference specifies neither a tag nor a digest.
+pub const DEFAULT_TAG: &str = "latest";
+
+const MAX_NAME_LENGTH: usize = 255;
+const MAX_TAG_LENGTH: usize = 128;
+
+/// A parsed image reference of the form `[registry/][namespace/]repository[:tag][@digest]`.
+///
+/// References are normalized the way docker does: images without a registry come from [`DOCKER_HUB_REGISTRY`],
+/// single component names on Docker Hub live in [`DOCKER_HUB_OFFICIAL_NAMESPACE`] and references without a tag or
+/// digest use [`DEFAULT_TAG`]. Its [`Display`] implementation renders the fully qualified reference.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct ImageRef {
+    registry: String,
+    path: String,
+    tag: Option<String>,
+    digest: Option<String>,
+}
+
+impl ImageRef {
+    /// Parses and normalizes the reference, failing with [`DockerError::InvalidArgument`] if it is malformed.
+    pub fn parse(reference: &str) -> Result<ImageRef, DockerError> {
+        let invalid =
+            |reason: String| DockerError::InvalidArgument(format!("image reference `{}` {}", reference, reason));
+        if reference.is_empty() {
+            return Err(invalid("is empty".to_owned()));
+        }
+
+        let (name, digest) = match reference.split_once('@') {
+            Some((name, digest)) => {
+                validate_digest(digest).map_err(&invalid)?;
+                (name, Some(digest.to_owned()))
+            }
+            None => (reference, None),
+        };
+        // A colon after the last slash separates the tag, earlier ones belong to the port of the registry.
+        let (name, tag) = match name.rfind(':') {
+            Some(colon) if !name[colon..].contains('/') => {
+                let tag = &name[colon + 1..];
+                validate_tag(tag).map_err(&invalid)?;
+                (&name[..colon], Some(tag.to_owned()))
+            }
+            _ => (name, None),
+        };
+        if name.len() > MAX_NAME_LENGTH {
+            return Err(invalid(format!(
+                "has a name longer than {} characters",
+                MAX_NAME_LENGTH
+            )));
+        }
+
+        let (registry, path) = match name.split_once('/') {
+            Some((domain, path)) if is_registry(domain) => {
+                validate_registry(domain).map_err(&invalid)?;
+                (domain, path)
+            }
+            _ => (DOCKER_HUB_REGISTRY, name),
+        };
+        for component in path.split('/') {
+            validate_path_component(component).map_err(&invalid)?;
+        }
+
+        let registry = match registry {
+            "index.docker.io" | "registry-1.docker.io" => DOCKER_HUB_REGISTRY,
+            registry => registry,
+        };
+        let path = if registry == DOCKER_HUB_REGISTRY && !path.contains('/') {
+            format!("{}/{}", DOCKER_HUB_OFFICIAL_NAMESPACE, path)
+        } else {
+            path.to_owned()
+        };
+        let tag = match (tag, &digest) {
+            (None, None) => Some(DEFAULT_TAG.to_owned()),
+            (tag, _) => tag,
+        };
+        Ok(ImageRef {
+            registry: registry.to_owned(),
+            path,
+            tag,
+            digest,
+        })
+    }
+
+    /// Returns the registry host, including its port if one was given, e.g. `docker.io` or `localhost:5000`.
+    pub fn registry(&self) -> &str {
+        &self.registry
+    }
+
+    /// Returns the path of the image without the registry, e.g. `library/redis`.
+    pub fn repository(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the part of the path before the image name, e.g. `library` for `library/redis`.
+    pub fn namespace(&self) -> Option<&str> {
+        self.path.rsplit_once('/').map(|(namespace, _)| namespace)
+    }
+
+    /// Returns the last component of the path, e.g. `redis` for `library/redis`.
+    pub fn name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+
+    /// Returns the tag, `None` only if the image is referenced by digest alone.
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
+
+    /// Returns the content digest, e.g. `sha256:...`, if the reference pins one.
+    pub fn digest(&self) -> Option<&str> {
+        self.digest.as_deref()
+    }
+
+    pub fn is_docker_hub(&self) -> bool {
+        self.registry == DOCKER_HUB_REGISTRY
+    }
+}
+
+impl FromStr for ImageRef {
+    type Err = DockerError;
+
+    fn from_str(reference: &str) -> Result<Self, Self::Err> {
+        ImageRef::parse(reference)
+    }
+}
+
+impl Display for ImageRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_fmt(format_args!("{}/{}", self.registry, self.path))?;
+        if let Some(tag) = &self.tag {
+            f.write_fmt(format_args!(":{}", tag))?;
+        }
+        if let Some(digest) = &self.digest {
+            f.write_fmt(format_args!("@{}", digest))?;
+        }
+        Ok(())
+    }
+}
+
+/// Docker only treats the first component as a registry if it looks like a host name.
+fn is_registry(component: &str) -> bool {
+    component.contains('.') || component.contains(':') || component == "localhost"
+}
+
+fn validate_registry(registry: &str) -> Result<(), String> {
+    let (host, port) = match registry.strip_prefix('[') {
+        Some(rest) => match rest.split_once(']') {
+            Some((ipv6, "")) => (ipv6, None),
+            Some((ipv6, port)) => (ipv6, Some(port.strip_prefix(':').unwrap_or(port))),
+            None => (registry, None),
+        },
+        None => match registry.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (registry, None),
+        },
+    };
+    let valid_host = if registry.starts_with('[') {
+        registry.contains(']') && !host.is_empty() && host.chars().all(|c| c.is_ascii_hexdigit() || c == ':')
+    } else {
+        !host.is_empty()
+            && host.split('.').all(|label| {
+                !label.is_empty()
+                    && !label.starts_with('-')
+                    && !label.ends_with('-')
+                    && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            })
+    };
+    if !valid_host {
+        return Err(format!("has an invalid registry host `{}`", host));
+    }
+    match port {
+        Some(port) if port.parse::<u16>().is_err() => Err(format!("has an invalid registry port `{}`", port)),
+        _ => Ok(()),
+    }
+}
+
+/// Path components are lowercase alphanumeric runs separated by `.`, `_`, `__` or any number of `-`.
+fn validate_path_component(component: &str) -> Result<(), String> {
+    if component.is_empty() {
+        return Err("has an empty path component".to_owned());
+    }
+    if component.chars().any(|c| c.is_ascii_uppercase()) {
+        return Err(format!(
+            "has the path component `{}` which must be lowercase",
+            component
+        ));
+    }
+    let bytes = component.as_bytes();
+    let mut valid = bytes[0].is_ascii_alphanumeric() && bytes[bytes.len() - 1].is_ascii_alphanumeric();
+    let mut separator = String::new();
+    for &b in bytes {
+        if b.is_ascii_lowercase() || b.is_ascii_digit() {
+            if !matches!(separator.as_str(), "" | "." | "_" | "__") && !separator.bytes().all(|s| s == b'-') {
+                valid = false;
+            }
+            separator.clear();
+        } else if b == b'.' || b == b'_' || b == b'-' {
+            separator.push(b as char);
+        } else {
+            valid = false;
+        }
+    }
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("has the invalid path component `{}`", component))
+    }
+}
+
+fn validate_tag(tag: &str) -> Result<(), String> {
+    let valid = !tag.is_empty()
+        && tag.len() <= MAX_TAG_LENGTH
+        && !tag.starts_with('.')
+        && !tag.starts_with('-')
+        && tag
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "has the invalid tag `{}`, tags consist of up to {} letters, digits, `_`, `.` and `-`",
+            tag, MAX_TAG_LENGTH
+        ))
+    }
+}
+
+/// Digests are `algorithm:hex`, sha256 digests must have 64 lowercase hexadecimal characters.
+fn validate_digest(digest: &str) -> Result<(), String> {
+    let invalid = || format!("has the invalid digest `{}`", digest);
+    let (algorithm, encoded) = digest.split_once(':').ok_or_else(invalid)?;
+    let valid_algorithm = !algorithm.is_empty()
+        && algorithm
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '+' || c == '.' || c == '_' || c == '-');
+    let valid_encoded = match algorithm {
+        "sha256" => encoded.len() == 64 && encoded.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c)),
+        "sha512" => encoded.len() == 128 && encoded.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c)),
+        _ => {
+            encoded.len() >= 32
+                && encoded
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '=' || c == '_' || c == '-')
+        }
+    };
+    if valid_algorithm && valid_encoded {
+        Ok(())
+    } else {
+        Err(invalid())
+    }
+}
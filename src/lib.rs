@@ -2,10 +2,32 @@ mod commands;
 mod container;
 mod docker_parse;
 mod errors;
+mod host;
 mod image;
+mod image_ref;
+mod limits;
+mod mount;
+mod naming;
+mod network;
+mod port;
+mod pull;
+mod tar;
+mod tunnel;
+mod volume;
 
 pub use commands::*;
 pub use container::*;
-use docker_parse::*;
+pub use docker_parse::{
+    ContainerConfig, ContainerHealth, ContainerHostConfig, ContainerInfo, ContainerMount, ContainerNetworkSettings,
+    ContainerState, EndpointSettings, HealthCheckResult, ImageInfo, PortMapping, RestartPolicyConfig,
+};
 pub use errors::*;
 pub use image::*;
+pub use image_ref::*;
+pub use limits::*;
+pub use mount::*;
+pub use network::*;
+pub use port::*;
+pub use pull::*;
+pub use tunnel::*;
+pub use volume::*;
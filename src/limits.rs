@@ -0,0 +1,200 @@
+use crate::DockerError;
+use std::fmt::{self, Display};
+
+/// Limits on the host resources a container may use.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ResourceLimits {
+    pub memory: Option<u64>,
+    pub memory_swap: Option<i64>,
+    pub cpus: Option<f64>,
+    pub cpu_quota: Option<i64>,
+    pub cpu_period: Option<u64>,
+    pub cpu_shares: Option<u64>,
+    pub cpuset_cpus: Option<String>,
+    pub pids_limit: Option<i64>,
+    pub shm_size: Option<u64>,
+    pub ulimits: Vec<Ulimit>,
+}
+
+impl ResourceLimits {
+    /// Limits the memory in bytes.
+    pub fn with_memory(mut self, bytes: u64) -> Self {
+        self.memory = Some(bytes);
+        self
+    }
+
+    /// Limits memory plus swap in bytes, `-1` allows unlimited swap.
+    pub fn with_memory_swap(mut self, bytes: i64) -> Self {
+        self.memory_swap = Some(bytes);
+        self
+    }
+
+    /// Limits the number of CPUs, e.g. `1.5`.
+    pub fn with_cpus(mut self, cpus: f64) -> Self {
+        self.cpus = Some(cpus);
+        self
+    }
+
+    /// Limits the CPU time in microseconds per [period](Self::with_cpu_period).
+    pub fn with_cpu_quota(mut self, quota: i64) -> Self {
+        self.cpu_quota = Some(quota);
+        self
+    }
+
+    /// Sets the CPU scheduling period in microseconds.
+    pub fn with_cpu_period(mut self, period: u64) -> Self {
+        self.cpu_period = Some(period);
+        self
+    }
+
+    /// Sets the relative CPU weight, `1024` by default.
+    pub fn with_cpu_shares(mut self, shares: u64) -> Self {
+        self.cpu_shares = Some(shares);
+        self
+    }
+
+    /// Restricts the container to the given CPUs, e.g. `0-2` or `1,3`.
+    pub fn with_cpuset_cpus<S: Into<String>>(mut self, cpus: S) -> Self {
+        self.cpuset_cpus = Some(cpus.into());
+        self
+    }
+
+    /// Limits the number of processes, `-1` for unlimited.
+    pub fn with_pids_limit(mut self, limit: i64) -> Self {
+        self.pids_limit = Some(limit);
+        self
+    }
+
+    /// Sets the size of `/dev/shm` in bytes.
+    pub fn with_shm_size(mut self, bytes: u64) -> Self {
+        self.shm_size = Some(bytes);
+        self
+    }
+
+    pub fn with_ulimit(mut self, ulimit: Ulimit) -> Self {
+        self.ulimits.push(ulimit);
+        self
+    }
+
+    /// Renders the limits as `docker run` arguments, failing if a limit can never be satisfied.
+    pub fn to_args(&self) -> Result<Vec<String>, DockerError> {
+        if let Some(cpus) = self.cpus {
+            if cpus.is_nan() || cpus <= 0.0 {
+                return Err(DockerError::InvalidArgument(format!(
+                    "cpus must be positive, got {}",
+                    cpus
+                )));
+            }
+        }
+        let mut args = vec![];
+        let mut push = |flag: &str, value: Option<String>| {
+            if let Some(value) = value {
+                args.push(flag.to_owned());
+                args.push(value);
+            }
+        };
+        push("--memory", self.memory.map(|v| v.to_string()));
+        push("--memory-swap", self.memory_swap.map(|v| v.to_string()));
+        push("--cpus", self.cpus.map(|v| v.to_string()));
+        push("--cpu-quota", self.cpu_quota.map(|v| v.to_string()));
+        push("--cpu-period", self.cpu_period.map(|v| v.to_string()));
+        push("--cpu-shares", self.cpu_shares.map(|v| v.to_string()));
+        push("--cpuset-cpus", self.cpuset_cpus.clone());
+        push("--pids-limit", self.pids_limit.map(|v| v.to_string()));
+        push("--shm-size", self.shm_size.map(|v| v.to_string()));
+        for ulimit in &self.ulimits {
+            push("--ulimit", Some(ulimit.to_string()));
+        }
+        Ok(args)
+    }
+}
+
+/// A limit on a resource of the processes of a container, e.g. the number of open files.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Ulimit {
+    pub name: String,
+    pub soft: i64,
+    pub hard: i64,
+}
+
+impl Ulimit {
+    pub fn new<S: Into<String>>(name: S, soft: i64, hard: i64) -> Self {
+        Self {
+            name: name.into(),
+            soft,
+            hard,
+        }
+    }
+}
+
+impl Display for Ulimit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_fmt(format_args!("{}={}:{}", self.name, self.soft, self.hard))
+    }
+}
+
+/// Privileges and isolation of a container.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct SecurityOptions {
+    pub read_only: bool,
+    pub privileged: bool,
+    pub cap_add: Vec<String>,
+    pub cap_drop: Vec<String>,
+    pub security_opt: Vec<String>,
+}
+
+impl SecurityOptions {
+    /// Mounts the root filesystem of the container read-only.
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Gives the container all capabilities and access to the devices of the host.
+    pub fn privileged(mut self) -> Self {
+        self.privileged = true;
+        self
+    }
+
+    /// Adds a Linux capability, e.g. `NET_ADMIN`.
+    pub fn with_cap_add<S: Into<String>>(mut self, capability: S) -> Self {
+        self.cap_add.push(capability.into());
+        self
+    }
+
+    /// Drops a Linux capability, `ALL` drops every capability not explicitly added.
+    pub fn with_cap_drop<S: Into<String>>(mut self, capability: S) -> Self {
+        self.cap_drop.push(capability.into());
+        self
+    }
+
+    /// Adds a security option, e.g. `no-new-privileges` or `seccomp=unconfined`.
+    pub fn with_security_opt<S: Into<String>>(mut self, option: S) -> Self {
+        self.security_opt.push(option.into());
+        self
+    }
+
+    /// Renders the options as `docker run` arguments.
+    pub(crate) fn to_args(&self) -> Vec<String> {
+        let mut args = vec![];
+        if self.read_only {
+            args.push("--read-only".to_owned());
+        }
+        if self.privileged {
+            args.push("--privileged".to_owned());
+        }
+        for capability in &self.cap_add {
+            args.push("--cap-add".to_owned());
+            args.push(capability.clone());
+        }
+        for capability in &self.cap_drop {
+            args.push("--cap-drop".to_owned());
+            args.push(capability.clone());
+        }
+        for option in &self.security_opt {
+            args.push("--security-opt".to_owned());
+            args.push(option.clone());
+        }
+        args
+    }
+}
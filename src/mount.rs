@@ -0,0 +1,268 @@
+use crate::DockerError;
+use std::{collections::BTreeMap, path::Path};
+
+/// A filesystem mount of a docker container, rendered as a `--mount` argument.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Mount {
+    Bind(BindMount),
+    Volume(VolumeMount),
+    Tmpfs(TmpfsMount),
+}
+
+impl Mount {
+    /// Mounts a file or directory of the host into the container.
+    pub fn bind<S: Into<String>, T: Into<String>>(source: S, target: T) -> BindMount {
+        BindMount {
+            source: source.into(),
+            target: target.into(),
+            read_only: false,
+            propagation: None,
+        }
+    }
+
+    /// Mounts the named volume into the container, it is created if it does not exist yet.
+    pub fn volume<S: Into<String>, T: Into<String>>(source: S, target: T) -> VolumeMount {
+        VolumeMount {
+            source: Some(source.into()),
+            target: target.into(),
+            read_only: false,
+            no_copy: false,
+            driver: None,
+            driver_opts: BTreeMap::new(),
+        }
+    }
+
+    /// Mounts a new anonymous volume into the container, it is removed together with the container.
+    pub fn anonymous_volume<T: Into<String>>(target: T) -> VolumeMount {
+        VolumeMount {
+            source: None,
+            target: target.into(),
+            read_only: false,
+            no_copy: false,
+            driver: None,
+            driver_opts: BTreeMap::new(),
+        }
+    }
+
+    /// Mounts a filesystem that only lives in the memory of the host into the container.
+    pub fn tmpfs<T: Into<String>>(target: T) -> TmpfsMount {
+        TmpfsMount {
+            target: target.into(),
+            size: None,
+            mode: None,
+        }
+    }
+
+    /// Returns the path the mount is placed at inside the container.
+    pub fn target(&self) -> &str {
+        match self {
+            Mount::Bind(mount) => &mount.target,
+            Mount::Volume(mount) => &mount.target,
+            Mount::Tmpfs(mount) => &mount.target,
+        }
+    }
+
+    /// Validates the mount and renders it as the value of a `--mount` argument.
+    pub fn to_arg(&self) -> Result<String, DockerError> {
+        let target = self.target();
+        if !target.starts_with('/') {
+            return Err(DockerError::InvalidArgument(format!(
+                "mount target {} is not an absolute path",
+                target
+            )));
+        }
+        let mut fields = vec![];
+        match self {
+            Mount::Bind(mount) => {
+                if !Path::new(&mount.source).is_absolute() {
+                    return Err(DockerError::InvalidArgument(format!(
+                        "bind mount source {} is not an absolute path",
+                        mount.source
+                    )));
+                }
+                fields.push("type=bind".to_owned());
+                fields.push(format!("source={}", mount.source));
+                fields.push(format!("target={}", mount.target));
+                if mount.read_only {
+                    fields.push("readonly".to_owned());
+                }
+                if let Some(propagation) = mount.propagation {
+                    fields.push(format!("bind-propagation={}", propagation.as_str()));
+                }
+            }
+            Mount::Volume(mount) => {
+                fields.push("type=volume".to_owned());
+                if let Some(source) = &mount.source {
+                    if source.is_empty() || source.contains('/') {
+                        return Err(DockerError::InvalidArgument(format!(
+                            "invalid volume name {:?}",
+                            source
+                        )));
+                    }
+                    fields.push(format!("source={}", source));
+                }
+                fields.push(format!("target={}", mount.target));
+                if mount.read_only {
+                    fields.push("readonly".to_owned());
+                }
+                if mount.no_copy {
+                    fields.push("volume-nocopy".to_owned());
+                }
+                if let Some(driver) = &mount.driver {
+                    fields.push(format!("volume-driver={}", driver));
+                }
+                for (key, value) in &mount.driver_opts {
+                    fields.push(format!("volume-opt={}={}", key, value));
+                }
+            }
+            Mount::Tmpfs(mount) => {
+                fields.push("type=tmpfs".to_owned());
+                fields.push(format!("target={}", mount.target));
+                if let Some(size) = mount.size {
+                    fields.push(format!("tmpfs-size={}", size));
+                }
+                if let Some(mode) = mount.mode {
+                    if mode > 0o7777 {
+                        return Err(DockerError::InvalidArgument(format!("invalid tmpfs mode {:o}", mode)));
+                    }
+                    fields.push(format!("tmpfs-mode={:o}", mode));
+                }
+            }
+        }
+        Ok(fields
+            .iter()
+            .map(|field| escape_field(field))
+            .collect::<Vec<_>>()
+            .join(","))
+    }
+}
+
+/// The `--mount` value is parsed as a CSV record, so fields containing commas or quotes have to be quoted.
+fn escape_field(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// A file or directory of the host mounted into the container.
+#[derive(Debug, PartialEq, Clone)]
+pub struct BindMount {
+    source: String,
+    target: String,
+    read_only: bool,
+    propagation: Option<BindPropagation>,
+}
+
+impl BindMount {
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    pub fn with_propagation(mut self, propagation: BindPropagation) -> Self {
+        self.propagation = Some(propagation);
+        self
+    }
+}
+
+impl From<BindMount> for Mount {
+    fn from(mount: BindMount) -> Self {
+        Mount::Bind(mount)
+    }
+}
+
+/// Defines whether mounts created below a bind mount are propagated between the host and the container.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum BindPropagation {
+    Private,
+    RPrivate,
+    Shared,
+    RShared,
+    Slave,
+    RSlave,
+}
+
+impl BindPropagation {
+    fn as_str(self) -> &'static str {
+        match self {
+            BindPropagation::Private => "private",
+            BindPropagation::RPrivate => "rprivate",
+            BindPropagation::Shared => "shared",
+            BindPropagation::RShared => "rshared",
+            BindPropagation::Slave => "slave",
+            BindPropagation::RSlave => "rslave",
+        }
+    }
+}
+
+/// A named or anonymous volume mounted into the container.
+#[derive(Debug, PartialEq, Clone)]
+pub struct VolumeMount {
+    source: Option<String>,
+    target: String,
+    read_only: bool,
+    no_copy: bool,
+    driver: Option<String>,
+    driver_opts: BTreeMap<String, String>,
+}
+
+impl VolumeMount {
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Does not populate a new volume with the contents the image has at the target path.
+    pub fn no_copy(mut self) -> Self {
+        self.no_copy = true;
+        self
+    }
+
+    /// Sets the driver used when the volume has to be created.
+    pub fn with_driver<S: Into<String>>(mut self, driver: S) -> Self {
+        self.driver = Some(driver.into());
+        self
+    }
+
+    /// Adds an option passed to the driver when the volume has to be created.
+    pub fn with_driver_opt<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.driver_opts.insert(key.into(), value.into());
+        self
+    }
+}
+
+impl From<VolumeMount> for Mount {
+    fn from(mount: VolumeMount) -> Self {
+        Mount::Volume(mount)
+    }
+}
+
+/// A filesystem in the memory of the host mounted into the container.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TmpfsMount {
+    target: String,
+    size: Option<u64>,
+    mode: Option<u32>,
+}
+
+impl TmpfsMount {
+    /// Limits the size of the filesystem in bytes.
+    pub fn with_size(mut self, bytes: u64) -> Self {
+        self.size = Some(bytes);
+        self
+    }
+
+    /// Sets the file mode of the mounted directory, e.g. `0o1777`.
+    pub fn with_mode(mut self, mode: u32) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+}
+
+impl From<TmpfsMount> for Mount {
+    fn from(mount: TmpfsMount) -> Self {
+        Mount::Tmpfs(mount)
+    }
+}
@@ -0,0 +1,39 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        OnceLock,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+static SESSION_ID: OnceLock<String> = OnceLock::new();
+
+/// Marks every container, network and volume created by hotto-docker.
+pub(crate) const MANAGED_LABEL: &str = "org.hotto-docker.managed";
+/// Identifies the test process that created a container, network or volume.
+pub(crate) const SESSION_ID_LABEL: &str = "org.hotto-docker.session-id";
+
+/// Appends a suffix to the prefix that is unique across concurrently running test processes.
+pub(crate) fn unique_name(prefix: &str) -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    format!(
+        "{}-{:x}{:08x}{:x}",
+        prefix,
+        std::process::id(),
+        nanos,
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Returns an id that is shared by everything created by this process.
+pub(crate) fn session_id() -> &'static str {
+    SESSION_ID.get_or_init(|| unique_name("session"))
+}
+
+/// Returns the labels added to every container, network and volume.
+pub(crate) fn default_labels() -> Vec<(&'static str, &'static str)> {
+    vec![(MANAGED_LABEL, "true"), (SESSION_ID_LABEL, session_id())]
+}
@@ -0,0 +1,126 @@
+use crate::{naming, DockerError, NetworkCommand};
+use std::collections::HashMap;
+
+/// The options a docker network is created with.
+#[derive(Debug, PartialEq, Clone)]
+pub struct NetworkOptions {
+    prefix: String,
+    driver: Option<String>,
+    subnet: Option<String>,
+    gateway: Option<String>,
+    internal: bool,
+    labels: HashMap<String, String>,
+}
+
+impl Default for NetworkOptions {
+    fn default() -> Self {
+        NetworkOptions::new("hotto-docker")
+    }
+}
+
+impl NetworkOptions {
+    /// The network name is the given prefix followed by a unique suffix.
+    pub fn new<S: Into<String>>(prefix: S) -> Self {
+        Self {
+            prefix: prefix.into(),
+            driver: None,
+            subnet: None,
+            gateway: None,
+            internal: false,
+            labels: HashMap::new(),
+        }
+    }
+
+    /// Sets the network driver, `bridge` if not set.
+    pub fn with_driver<S: Into<String>>(mut self, driver: S) -> Self {
+        self.driver = Some(driver.into());
+        self
+    }
+
+    /// Sets the subnet in CIDR notation, e.g. `172.28.0.0/16`.
+    pub fn with_subnet<S: Into<String>>(mut self, subnet: S) -> Self {
+        self.subnet = Some(subnet.into());
+        self
+    }
+
+    /// Sets the gateway of the subnet, requires a subnet.
+    pub fn with_gateway<S: Into<String>>(mut self, gateway: S) -> Self {
+        self.gateway = Some(gateway.into());
+        self
+    }
+
+    /// Restricts external access to the network, containers can only reach each other.
+    pub fn internal(mut self) -> Self {
+        self.internal = true;
+        self
+    }
+
+    pub fn with_label<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn driver(&self) -> Option<&str> {
+        self.driver.as_deref()
+    }
+
+    pub fn subnet(&self) -> Option<&str> {
+        self.subnet.as_deref()
+    }
+
+    pub fn gateway(&self) -> Option<&str> {
+        self.gateway.as_deref()
+    }
+
+    pub fn is_internal(&self) -> bool {
+        self.internal
+    }
+
+    pub fn labels(&self) -> &HashMap<String, String> {
+        &self.labels
+    }
+}
+
+/// A user-defined docker network with a unique name, removed when it goes out of scope.
+///
+/// Containers join the network through [`GenericImage::with_network`] when they are created or through
+/// [`DockerContainer::connect_to_network`] while they run, and reach each other through their network aliases.
+///
+/// [`GenericImage::with_network`]: crate::GenericImage::with_network
+/// [`DockerContainer::connect_to_network`]: crate::DockerContainer::connect_to_network
+pub struct DockerNetwork {
+    id: String,
+    name: String,
+}
+
+impl DockerNetwork {
+    pub async fn new(options: NetworkOptions) -> Result<Self, DockerError> {
+        let name = naming::unique_name(&options.prefix);
+        let id = NetworkCommand::create_network(&name, &options).await?;
+        log::debug!("Created docker network {} ({})", name, id);
+        Ok(DockerNetwork { id, name })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// The destructor implementation for a DockerNetwork.
+///
+/// Like containers, networks are kept when the `KEEP_CONTAINERS` environment variable is set to `true` and removed
+/// otherwise. Containers still connected to the network prevent its removal, so they should be dropped first.
+impl Drop for DockerNetwork {
+    fn drop(&mut self) {
+        if crate::container::keep_containers() {
+            log::debug!("Keeping docker network {}", self.name);
+        } else {
+            log::debug!("Removing docker network {}", self.name);
+            NetworkCommand::rm_network(&self.name);
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use std::{
+    fmt::{self, Display},
+    net::IpAddr,
+};
+
+/// The transport protocol of a container port.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+pub enum Protocol {
+    Tcp,
+    Udp,
+    Sctp,
+}
+
+impl Protocol {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Protocol::Tcp => "tcp",
+            Protocol::Udp => "udp",
+            Protocol::Sctp => "sctp",
+        }
+    }
+
+    /// Parses the protocol suffix docker uses in port specifications, e.g. `udp` in `53/udp`.
+    pub fn parse(protocol: &str) -> Option<Protocol> {
+        match protocol {
+            "tcp" => Some(Protocol::Tcp),
+            "udp" => Some(Protocol::Udp),
+            "sctp" => Some(Protocol::Sctp),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A container port, rendered as `port/protocol`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct ExposedPort {
+    pub port: u16,
+    pub protocol: Protocol,
+}
+
+impl ExposedPort {
+    pub fn tcp(port: u16) -> Self {
+        Self {
+            port,
+            protocol: Protocol::Tcp,
+        }
+    }
+
+    pub fn udp(port: u16) -> Self {
+        Self {
+            port,
+            protocol: Protocol::Udp,
+        }
+    }
+}
+
+impl From<u16> for ExposedPort {
+    fn from(port: u16) -> Self {
+        ExposedPort::tcp(port)
+    }
+}
+
+impl Display for ExposedPort {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_fmt(format_args!("{}/{}", self.port, self.protocol))
+    }
+}
+
+/// Publishes a container port on the host, rendered as a `-p host_ip:host_port:container_port/protocol` argument.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PortBinding {
+    pub host_ip: Option<IpAddr>,
+    pub host_port: Option<u16>,
+    pub container_port: ExposedPort,
+}
+
+impl PortBinding {
+    /// Publishes the container port on a random host port on all interfaces.
+    pub fn new<P: Into<ExposedPort>>(container_port: P) -> Self {
+        Self {
+            host_ip: None,
+            host_port: None,
+            container_port: container_port.into(),
+        }
+    }
+
+    /// Publishes the container port on the given host port on all interfaces.
+    pub fn fixed<P: Into<ExposedPort>>(host_port: u16, container_port: P) -> Self {
+        PortBinding::new(container_port).with_host_port(host_port)
+    }
+
+    pub fn with_host_port(mut self, host_port: u16) -> Self {
+        self.host_port = Some(host_port);
+        self
+    }
+
+    /// Only publishes the port on the given host interface, e.g. `127.0.0.1`.
+    pub fn with_host_ip(mut self, host_ip: IpAddr) -> Self {
+        self.host_ip = Some(host_ip);
+        self
+    }
+
+    /// Renders the binding as the value of a `-p` argument.
+    pub fn to_arg(&self) -> String {
+        let host_port = self.host_port.map(|port| port.to_string()).unwrap_or_default();
+        match self.host_ip {
+            Some(IpAddr::V4(ip)) => format!("{}:{}:{}", ip, host_port, self.container_port),
+            Some(IpAddr::V6(ip)) => format!("[{}]:{}:{}", ip, host_port, self.container_port),
+            None if self.host_port.is_some() => format!("{}:{}", host_port, self.container_port),
+            None => self.container_port.to_string(),
+        }
+    }
+}
+
+/// The address a container port is published on by the host.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct HostBinding {
+    pub ip: IpAddr,
+    pub port: u16,
+}
+
+/// The IP version of a host interface.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum IpVersion {
+    V4,
+    V6,
+}
+
+impl HostBinding {
+    pub fn ip_version(&self) -> IpVersion {
+        match self.ip {
+            IpAddr::V4(_) => IpVersion::V4,
+            IpAddr::V6(_) => IpVersion::V6,
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{DockerError, Image, ImageInspectCommand, PullCommand};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+/// The number of images [`prepull`] pulls at the same time.
+pub const DEFAULT_PREPULL_CONCURRENCY: usize = 4;
+
+/// Defines when an image is pulled before a container is run from it.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum PullPolicy {
+    /// Pulls the image every time, picking up updates of moving tags like `latest`.
+    Always,
+    /// Only pulls the image if it is not present locally, or only for a different platform than the requested one.
+    #[default]
+    IfMissing,
+    /// Never pulls the image, running it fails if it is not present locally.
+    Never,
+}
+
+/// A progress update reported by `docker pull`.
+///
+/// Docker only prints byte counts and progress bars when its output is a terminal. Its output is piped here, so
+/// updates only carry the status of each layer, e.g. `Pulling fs layer`, `Download complete` or `Pull complete`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PullProgress {
+    /// The short id of the layer the update is about, `None` for updates about the whole image.
+    pub layer: Option<String>,
+    /// The status text, e.g. `Verifying Checksum` or `Status: Downloaded newer image for redis:5-alpine`.
+    pub status: String,
+}
+
+impl PullProgress {
+    /// Parses a line of `docker pull` output, e.g. `c9b1b535fdd9: Pull complete`.
+    pub fn parse(line: &str) -> PullProgress {
+        let line = line.trim();
+        match line.split_once(": ") {
+            Some((layer, status)) if is_layer_id(layer) => PullProgress {
+                layer: Some(layer.to_owned()),
+                status: status.to_owned(),
+            },
+            _ => PullProgress {
+                layer: None,
+                status: line.to_owned(),
+            },
+        }
+    }
+}
+
+fn is_layer_id(id: &str) -> bool {
+    id.len() == 12 && id.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+type ProgressHandler = Box<dyn Fn(&PullProgress) + Send + Sync>;
+
+/// Makes images available locally according to a [`PullPolicy`].
+///
+/// Containers are started through [`DockerContainer::new`](crate::DockerContainer::new) only after the image was
+/// pulled, so the time spent pulling does not count against the [`WaitFor`](crate::WaitFor) deadline.
+pub struct ImagePuller {
+    policy: PullPolicy,
+    platform: Option<String>,
+    on_progress: Option<ProgressHandler>,
+}
+
+impl ImagePuller {
+    pub fn new(policy: PullPolicy) -> Self {
+        ImagePuller {
+            policy,
+            platform: None,
+            on_progress: None,
+        }
+    }
+
+    /// Pulls the image for the given platform instead of the one of the docker daemon.
+    pub fn with_platform<S: Into<String>>(mut self, platform: S) -> Self {
+        self.platform = Some(platform.into());
+        self
+    }
+
+    /// Calls the function with every progress update while pulling, updates are logged otherwise.
+    pub fn on_progress<F>(mut self, on_progress: F) -> Self
+    where
+        F: Fn(&PullProgress) + Send + Sync + 'static,
+    {
+        self.on_progress = Some(Box::new(on_progress));
+        self
+    }
+
+    pub fn policy(&self) -> PullPolicy {
+        self.policy
+    }
+
+    /// Makes the image available according to the policy and returns whether it was pulled.
+    pub async fn apply(&self, descriptor: &str) -> Result<bool, DockerError> {
+        match self.policy {
+            PullPolicy::Always => {}
+            PullPolicy::IfMissing => {
+                // A local image built for another platform counts as missing, pulling replaces it.
+                let present = ImageInspectCommand::inspect_image(descriptor).await?;
+                let usable = match (&present, &self.platform) {
+                    (Some(info), Some(platform)) => info.matches_platform(platform),
+                    (present, None) => present.is_some(),
+                    (None, _) => false,
+                };
+                if usable {
+                    return Ok(false);
+                }
+            }
+            PullPolicy::Never => {
+                return match ImageInspectCommand::inspect_image(descriptor).await? {
+                    Some(_) => Ok(false),
+                    None => Err(DockerError::ImageNotFound(descriptor.to_owned())),
+                };
+            }
+        }
+        self.pull(descriptor).await?;
+        Ok(true)
+    }
+
+    /// Pulls the image regardless of the policy.
+    pub async fn pull(&self, descriptor: &str) -> Result<(), DockerError> {
+        log::debug!("Pulling image {}", descriptor);
+        let start = Instant::now();
+        PullCommand::pull(descriptor, self.platform.as_deref(), |progress| {
+            match &self.on_progress {
+                Some(on_progress) => on_progress(&progress),
+                None => log::trace!(
+                    "pull:{} > {}: {}",
+                    descriptor,
+                    progress.layer.as_deref().unwrap_or("image"),
+                    progress.status
+                ),
+            }
+        })
+        .await?;
+        log::debug!("Pulled image {} in {:?}", descriptor, start.elapsed());
+        Ok(())
+    }
+}
+
+/// Makes all images available locally according to their [`Image::pull_policy`] before any container is started.
+///
+/// Calling this once at the start of a test suite keeps its first tests from running into timeouts while pulling.
+/// Returns the descriptors of the pulled images. See [`prepull_with_concurrency`].
+pub async fn prepull<I: Image>(images: &[I]) -> Result<Vec<String>, DockerError> {
+    prepull_with_concurrency(images, DEFAULT_PREPULL_CONCURRENCY).await
+}
+
+/// Like [`prepull`], but pulls at most `concurrency` images at the same time.
+///
+/// All images are attempted even if some of them fail, the failures are reported together as
+/// [`DockerError::PullFailed`].
+pub async fn prepull_with_concurrency<I: Image>(images: &[I], concurrency: usize) -> Result<Vec<String>, DockerError> {
+    let mut queue = VecDeque::new();
+    for image in images {
+        let request = (image.descriptor(), image.platform(), image.pull_policy());
+        if !queue.contains(&request) {
+            queue.push_back(request);
+        }
+    }
+    log::debug!("Prepulling {} images", queue.len());
+    let queue = Arc::new(Mutex::new(queue));
+
+    let workers: Vec<_> = (0..concurrency.max(1))
+        .map(|_| {
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                let mut results = vec![];
+                loop {
+                    let next = queue.lock().expect("prepull queue poisoned").pop_front();
+                    let (descriptor, platform, policy) = match next {
+                        Some(request) => request,
+                        None => break,
+                    };
+                    let mut puller = ImagePuller::new(policy);
+                    if let Some(platform) = platform {
+                        puller = puller.with_platform(platform);
+                    }
+                    let result = puller.apply(&descriptor).await;
+                    results.push((descriptor, result));
+                }
+                results
+            })
+        })
+        .collect();
+
+    let mut pulled = vec![];
+    let mut failures = vec![];
+    for worker in workers {
+        for (descriptor, result) in worker.await.expect("prepull worker panicked") {
+            match result {
+                Ok(true) => pulled.push(descriptor),
+                Ok(false) => {}
+                Err(err) => {
+                    log::error!("Failed to prepull image {}: {}", descriptor, err);
+                    failures.push((descriptor, err));
+                }
+            }
+        }
+    }
+    if failures.is_empty() {
+        Ok(pulled)
+    } else {
+        Err(DockerError::PullFailed(failures))
+    }
+}
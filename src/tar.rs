@@ -0,0 +1,99 @@
+//! Just enough of the ustar format to move single files through `docker cp`.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const BLOCK_SIZE: usize = 512;
+
+/// The longest file name the name field of a header holds.
+pub(crate) const MAX_NAME_LENGTH: usize = 100;
+/// The largest file size the 11 octal digits of the size field hold, 8 GiB minus one byte.
+pub(crate) const MAX_FILE_SIZE: u64 = 0o77777777777;
+/// The largest user or group id the 7 octal digits of the id fields hold.
+pub(crate) const MAX_ID: u32 = 0o7777777;
+
+/// Builds a tar archive containing a single regular file.
+///
+/// The caller ensures the name, size and ids are within [`MAX_NAME_LENGTH`], [`MAX_FILE_SIZE`] and [`MAX_ID`],
+/// larger values would be cut off and corrupt the header.
+pub(crate) fn single_file_archive(name: &str, data: &[u8], mode: u32, uid: u32, gid: u32) -> Vec<u8> {
+    let mut header = [0u8; BLOCK_SIZE];
+    write_field(&mut header[0..100], name.as_bytes());
+    write_octal(&mut header[100..108], u64::from(mode & 0o7777));
+    write_octal(&mut header[108..116], u64::from(uid));
+    write_octal(&mut header[116..124], u64::from(gid));
+    write_octal(&mut header[124..136], data.len() as u64);
+    let mtime = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    write_octal(&mut header[136..148], mtime);
+    header[156] = b'0';
+    write_field(&mut header[257..263], b"ustar\0");
+    write_field(&mut header[263..265], b"00");
+
+    // The checksum is computed with the checksum field itself filled with spaces.
+    header[148..156].copy_from_slice(b"        ");
+    let checksum: u32 = header.iter().map(|&b| u32::from(b)).sum();
+    write_octal(&mut header[148..155], u64::from(checksum));
+
+    let mut archive = Vec::with_capacity(BLOCK_SIZE * 3 + data.len());
+    archive.extend_from_slice(&header);
+    archive.extend_from_slice(data);
+    archive.resize(archive.len() + padding(data.len()), 0);
+    // Two empty blocks mark the end of the archive.
+    archive.resize(archive.len() + BLOCK_SIZE * 2, 0);
+    archive
+}
+
+fn write_field(field: &mut [u8], value: &[u8]) {
+    let len = value.len().min(field.len());
+    field[..len].copy_from_slice(&value[..len]);
+}
+
+fn write_octal(field: &mut [u8], value: u64) {
+    let digits = format!("{:0width$o}", value, width = field.len() - 1);
+    write_field(field, digits.as_bytes());
+}
+
+fn padding(len: usize) -> usize {
+    (BLOCK_SIZE - len % BLOCK_SIZE) % BLOCK_SIZE
+}
+
+/// Returns the contents of the first regular file in a tar archive.
+///
+/// Fails with the type flag of the first entry if it is not a regular file, e.g. `5` for a directory.
+pub(crate) fn first_file_contents(archive: &[u8]) -> Result<Vec<u8>, String> {
+    let mut offset = 0;
+    while offset + BLOCK_SIZE <= archive.len() {
+        let header = &archive[offset..offset + BLOCK_SIZE];
+        if header.iter().all(|&b| b == 0) {
+            break;
+        }
+        let size = parse_size(&header[124..136])?;
+        let start = offset + BLOCK_SIZE;
+        let end = start + size;
+        if end > archive.len() {
+            return Err("truncated tar archive".to_owned());
+        }
+        match header[156] {
+            b'0' | 0 => return Ok(archive[start..end].to_vec()),
+            // Extended headers only describe the next entry.
+            b'x' | b'g' | b'L' | b'K' => offset = end + padding(size),
+            flag => return Err(format!("entry is not a regular file, type flag {}", flag as char)),
+        }
+    }
+    Err("tar archive contains no file".to_owned())
+}
+
+fn parse_size(field: &[u8]) -> Result<usize, String> {
+    // Sizes that do not fit into octal digits are stored as big-endian binary with the high bit set.
+    if field[0] & 0x80 != 0 {
+        let size = field[1..]
+            .iter()
+            .fold(u64::from(field[0] & 0x7f), |acc, &b| (acc << 8) | u64::from(b));
+        return Ok(size as usize);
+    }
+    let digits = String::from_utf8_lossy(field);
+    let digits = digits.trim_matches(|c: char| c == '\0' || c == ' ');
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    usize::from_str_radix(digits, 8).map_err(|_| format!("invalid tar entry size {}", digits))
+}
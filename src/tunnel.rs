@@ -0,0 +1,217 @@
+use crate::{commands, naming, DockerContainer, DockerError, ExecRequest, GenericImage, Image, WaitFor};
+use std::{
+    fs,
+    io::{BufRead, BufReader, Read},
+    path::PathBuf,
+    process::{Child, Command as StdCommand, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+use tokio::process::Command;
+
+/// The hostname under which containers reach the ports exposed through a [`HostTunnel`].
+pub const HOST_TUNNEL_HOSTNAME: &str = "host.hotto-docker.internal";
+
+const SSHD_IMAGE: &str = "testcontainers/sshd:1.1.0";
+const SSH_PORT: u16 = 22;
+const READY_TIMEOUT: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Starts sshd with the public key of the tunnel as the only way to log in. Gateway ports let the reverse forwards
+/// listen on all interfaces of the sidecar instead of only on its loopback interface.
+const SSHD_SCRIPT: &str = "echo \"root:$ROOT_PASSWORD\" | chpasswd \
+    && mkdir -p /root/.ssh && chmod 700 /root/.ssh \
+    && echo \"$AUTHORIZED_KEY\" > /root/.ssh/authorized_keys && chmod 600 /root/.ssh/authorized_keys \
+    && exec /usr/sbin/sshd -D -e -o PermitRootLogin=prohibit-password -o PasswordAuthentication=no \
+    -o AddressFamily=inet -o GatewayPorts=yes -o AllowTcpForwarding=yes";
+
+/// Makes ports of the host reachable from containers on a network as [`HOST_TUNNEL_HOSTNAME`].
+///
+/// Unlike [`GenericImage::with_host_gateway`], this does not require containers to be able to open connections to
+/// the host. Like testcontainers' `exposeHostPorts`, an sshd sidecar joins the network and an `ssh` client on the
+/// host asks it to listen on the given ports through reverse forwards. Every connection accepted by the sidecar is
+/// multiplexed over that single SSH connection to the same port on localhost of the host, so the ports stay open for
+/// any number of concurrent connections.
+///
+/// Requires the OpenSSH client, `ssh` and `ssh-keygen`, on the host.
+pub struct HostTunnel {
+    ports: Vec<u16>,
+    ssh: Child,
+    key_dir: PathBuf,
+    sidecar: DockerContainer<GenericImage>,
+}
+
+impl HostTunnel {
+    /// Starts the sidecar on the given network and waits until all ports are listening.
+    pub async fn new(network: &str, ports: &[u16]) -> Result<Self, DockerError> {
+        let key_dir = std::env::temp_dir().join(naming::unique_name("hotto-docker-tunnel"));
+        fs::create_dir_all(&key_dir)?;
+        let result = HostTunnel::start(network, ports, key_dir.clone()).await;
+        if result.is_err() {
+            let _ = fs::remove_dir_all(&key_dir);
+        }
+        result
+    }
+
+    async fn start(network: &str, ports: &[u16], key_dir: PathBuf) -> Result<Self, DockerError> {
+        let key = key_dir.join("id_ed25519");
+        let mut keygen = Command::new("ssh-keygen");
+        keygen
+            .args(["-q", "-t", "ed25519", "-N", "", "-C", "hotto-docker-tunnel", "-f"])
+            .arg(&key);
+        commands::execute(&mut keygen).await?;
+        let public_key = fs::read_to_string(key.with_extension("pub"))?;
+
+        let image = GenericImage::new(SSHD_IMAGE)
+            .with_container_name("hotto-docker-tunnel")
+            .with_network(network)
+            .with_network_alias(HOST_TUNNEL_HOSTNAME)
+            .with_env_var("AUTHORIZED_KEY", public_key.trim())
+            .with_env_var("ROOT_PASSWORD", naming::unique_name("tunnel"))
+            .with_entrypoint("sh")
+            .with_args(vec!["-c".to_owned(), SSHD_SCRIPT.to_owned()])
+            .with_exposed_port(SSH_PORT)
+            .with_wait_for(WaitFor::message_on_stderr(
+                "Server listening on",
+                READY_TIMEOUT.as_secs(),
+            ));
+        let sidecar = DockerContainer::new(image).await?;
+        let address = sidecar.get_host_address(SSH_PORT).await.ok_or_else(|| {
+            DockerError::UnexpectedOutput(format!("ssh port of tunnel {} is not published", sidecar.id()))
+        })?;
+
+        let mut command = StdCommand::new("ssh");
+        command
+            .args(["-N", "-T", "-l", "root", "-i"])
+            .arg(&key)
+            .arg("-p")
+            .arg(address.port().to_string())
+            .args([
+                "-o",
+                "BatchMode=yes",
+                "-o",
+                "IdentitiesOnly=yes",
+                "-o",
+                "StrictHostKeyChecking=no",
+                "-o",
+                "UserKnownHostsFile=/dev/null",
+                "-o",
+                "ExitOnForwardFailure=yes",
+                "-o",
+                "ServerAliveInterval=10",
+                "-o",
+                "LogLevel=ERROR",
+            ]);
+        for port in ports {
+            log::debug!(
+                "Exposing host port {} on network {} through tunnel {}",
+                port,
+                network,
+                sidecar.id()
+            );
+            command.arg("-R").arg(format!("0.0.0.0:{}:127.0.0.1:{}", port, port));
+        }
+        command
+            .arg(address.ip().to_string())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+        log::debug!("Executing command: {:?}", command);
+        let ssh = command.spawn()?;
+
+        let mut tunnel = HostTunnel {
+            ports: ports.to_vec(),
+            ssh,
+            key_dir,
+            sidecar,
+        };
+        tunnel.wait_until_listening(&format!("{:?}", command)).await?;
+
+        let stderr = tunnel.ssh.stderr.take().expect("failed to unwrap stderr ssh command");
+        let id = tunnel.sidecar.id();
+        thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                log::warn!("tunnel:{} > {}", id, line);
+            }
+        });
+        Ok(tunnel)
+    }
+
+    /// Polls the listening sockets of the sidecar until every port was forwarded or ssh gave up.
+    async fn wait_until_listening(&mut self, command: &str) -> Result<(), DockerError> {
+        let deadline = Instant::now() + READY_TIMEOUT;
+        loop {
+            if let Some(status) = self.ssh.try_wait()? {
+                let mut stderr = String::new();
+                if let Some(mut pipe) = self.ssh.stderr.take() {
+                    pipe.read_to_string(&mut stderr)?;
+                }
+                return Err(DockerError::CommandFailed {
+                    command: command.to_owned(),
+                    code: status.code(),
+                    stderr,
+                });
+            }
+            let output = self
+                .sidecar
+                .exec(ExecRequest::new(vec!["cat", "/proc/net/tcp"]))
+                .await?;
+            let listening = listening_ports(&output.stdout_lossy());
+            if self.ports.iter().all(|port| listening.contains(port)) {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                log::error!(
+                    "Tunnel {} did not start listening within {:?}",
+                    self.sidecar.id(),
+                    READY_TIMEOUT
+                );
+                return Err(DockerError::Timeout);
+            }
+            tokio::time::delay_for(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Returns the hostname under which containers on the network reach the host ports.
+    pub fn hostname(&self) -> &'static str {
+        HOST_TUNNEL_HOSTNAME
+    }
+
+    pub fn ports(&self) -> &[u16] {
+        &self.ports
+    }
+
+    pub fn sidecar(&self) -> &DockerContainer<GenericImage> {
+        &self.sidecar
+    }
+}
+
+/// Closes the SSH connection, which ends all forwarded connections. The sidecar is removed afterwards like any
+/// other container.
+impl Drop for HostTunnel {
+    fn drop(&mut self) {
+        log::debug!("Stopping tunnel {}", self.sidecar.id());
+        let _ = self.ssh.kill();
+        let _ = self.ssh.wait();
+        let _ = fs::remove_dir_all(&self.key_dir);
+    }
+}
+
+/// Returns the ports of the listening sockets in the contents of `/proc/net/tcp`.
+fn listening_ports(sockets: &str) -> Vec<u16> {
+    const LISTEN: &str = "0A";
+    sockets
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<_> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                [_, local, _, LISTEN, ..] => {
+                    let (_, port) = local.split_once(':')?;
+                    u16::from_str_radix(port, 16).ok()
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,98 @@
+use crate::{naming, DockerError, Mount, VolumeCommand, VolumeMount};
+use std::collections::HashMap;
+
+/// The options a docker volume is created with.
+#[derive(Debug, PartialEq, Clone)]
+pub struct VolumeOptions {
+    prefix: String,
+    driver: Option<String>,
+    driver_opts: HashMap<String, String>,
+    labels: HashMap<String, String>,
+}
+
+impl Default for VolumeOptions {
+    fn default() -> Self {
+        VolumeOptions::new("hotto-docker")
+    }
+}
+
+impl VolumeOptions {
+    /// The volume name is the given prefix followed by a unique suffix.
+    pub fn new<S: Into<String>>(prefix: S) -> Self {
+        Self {
+            prefix: prefix.into(),
+            driver: None,
+            driver_opts: HashMap::new(),
+            labels: HashMap::new(),
+        }
+    }
+
+    /// Sets the volume driver, `local` if not set.
+    pub fn with_driver<S: Into<String>>(mut self, driver: S) -> Self {
+        self.driver = Some(driver.into());
+        self
+    }
+
+    pub fn with_driver_opt<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.driver_opts.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn with_label<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn driver(&self) -> Option<&str> {
+        self.driver.as_deref()
+    }
+
+    pub fn driver_opts(&self) -> &HashMap<String, String> {
+        &self.driver_opts
+    }
+
+    pub fn labels(&self) -> &HashMap<String, String> {
+        &self.labels
+    }
+}
+
+/// A named docker volume with a unique name, removed when it goes out of scope.
+///
+/// The volume outlives the containers it is mounted into, so data written by one container can be read by a
+/// container replacing it, e.g. to test a service upgrade.
+pub struct DockerVolume {
+    name: String,
+}
+
+impl DockerVolume {
+    pub async fn new(options: VolumeOptions) -> Result<Self, DockerError> {
+        let name = naming::unique_name(&options.prefix);
+        VolumeCommand::create_volume(&name, &options).await?;
+        log::debug!("Created docker volume {}", name);
+        Ok(DockerVolume { name })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns a mount of this volume at the given path inside a container.
+    pub fn mount<T: Into<String>>(&self, target: T) -> VolumeMount {
+        Mount::volume(self.name.clone(), target)
+    }
+}
+
+/// The destructor implementation for a DockerVolume.
+///
+/// Like containers, volumes are kept when the `KEEP_CONTAINERS` environment variable is set to `true` and removed
+/// otherwise. Containers still using the volume prevent its removal, so they should be dropped first.
+impl Drop for DockerVolume {
+    fn drop(&mut self) {
+        if crate::container::keep_containers() {
+            log::debug!("Keeping docker volume {}", self.name);
+        } else {
+            log::debug!("Removing docker volume {}", self.name);
+            VolumeCommand::rm_volume(&self.name);
+        }
+    }
+}
@@ -1,11 +1,20 @@
 use hotto_docker::*;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
+
+fn init_logger() {
+    let _ = pretty_env_logger::formatted_builder()
+        .filter_level(log::LevelFilter::Debug)
+        .try_init();
+}
+
+fn redis_image() -> GenericImage {
+    GenericImage::new("redis:5-alpine").with_wait_for(WaitFor::message_on_stdout("Ready to accept connections", 20))
+}
 
 #[tokio::test]
 async fn test_generic_image() {
-    pretty_env_logger::formatted_builder()
-        .filter_level(log::LevelFilter::Debug)
-        .try_init()
-        .unwrap();
+    init_logger();
 
     let image = GenericImage::new("postgres:11-alpine")
         .with_wait_for(WaitFor::message_on_stderr(
@@ -20,3 +29,561 @@ async fn test_generic_image() {
     container.run_background_logs_stderr().await;
     tokio::time::delay_for(std::time::Duration::from_secs(10)).await;
 }
+
+#[tokio::test]
+async fn test_container_lifecycle() {
+    init_logger();
+
+    let container = DockerContainer::new(redis_image()).await.unwrap();
+    container.pause().await.unwrap();
+    let state = container.inspect().await.unwrap().state;
+    assert_eq!(state.status, "paused");
+    assert!(state.paused);
+
+    container.unpause().await.unwrap();
+    let state = container.inspect().await.unwrap().state;
+    assert_eq!(state.status, "running");
+    assert!(!state.paused);
+
+    container.restart().await.unwrap();
+    assert!(container.inspect().await.unwrap().state.running);
+
+    container.stop_with_timeout(Duration::from_secs(1)).await.unwrap();
+    let state = container.inspect().await.unwrap().state;
+    assert_eq!(state.status, "exited");
+    assert!(!state.running);
+
+    container.start().await.unwrap();
+    assert!(container.inspect().await.unwrap().state.running);
+
+    container.kill("SIGKILL").await.unwrap();
+    let status = container.wait_for_exit(Duration::from_secs(10)).await.unwrap();
+    assert_eq!(status.code(), 137);
+    assert_eq!(container.inspect().await.unwrap().state.status, "exited");
+}
+
+#[tokio::test]
+async fn test_wait_for_exit() {
+    init_logger();
+
+    let image = GenericImage::new("alpine:3.11").with_args(vec!["sh".to_owned(), "-c".to_owned(), "exit 3".to_owned()]);
+    let container = DockerContainer::new(image).await.unwrap();
+    let status = container.wait_for_exit(Duration::from_secs(10)).await.unwrap();
+    assert_eq!(status.code(), 3);
+    assert!(!status.success());
+
+    let info = InspectCommand::get_container_info(&container.id()).await;
+    assert_eq!(info.state.status, "exited");
+    assert_eq!(info.state.exit_code, 3);
+}
+
+#[tokio::test]
+async fn test_run_once() {
+    init_logger();
+
+    let image = GenericImage::new("alpine:3.11").with_args(vec![
+        "sh".to_owned(),
+        "-c".to_owned(),
+        "echo out; echo err >&2; exit 2".to_owned(),
+    ]);
+    let output = run_once(&image).await.unwrap();
+    assert_eq!(output.exit_code, 2);
+    assert_eq!(output.stdout_lossy(), "out\n");
+    assert_eq!(output.stderr_lossy(), "err\n");
+}
+
+#[tokio::test]
+async fn test_exec() {
+    init_logger();
+
+    let container = DockerContainer::new(redis_image()).await.unwrap();
+    let output = container
+        .exec(ExecRequest {
+            cmd: vec![
+                "sh".to_owned(),
+                "-c".to_owned(),
+                "echo $GREETING from $(pwd); exit 4".to_owned(),
+            ],
+            env: vec![("GREETING".to_owned(), "hello".to_owned())].into_iter().collect(),
+            workdir: Some("/tmp".to_owned()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(output.exit_code, 4);
+    assert_eq!(output.stdout_lossy(), "hello from /tmp\n");
+
+    let mut process = container
+        .exec_streaming(ExecRequest::new(vec!["redis-cli", "ping"]))
+        .unwrap();
+    let mut lines = tokio::io::BufReader::new(process.take_stdout().unwrap()).lines();
+    assert_eq!(lines.next_line().await.unwrap(), Some("PONG".to_owned()));
+    assert!(process.wait().await.unwrap().success());
+}
+
+#[tokio::test]
+async fn test_exec_interactive() {
+    init_logger();
+
+    let container = DockerContainer::new(redis_image()).await.unwrap();
+    let mut process = container
+        .exec_interactive(ExecRequest::new(vec!["redis-cli", "--pipe"]))
+        .unwrap();
+    let mut stdin = process.take_stdin().unwrap();
+    stdin.write_all(b"SET key value\r\n").await.unwrap();
+    drop(stdin);
+
+    let mut stdout = String::new();
+    process
+        .take_stdout()
+        .unwrap()
+        .read_to_string(&mut stdout)
+        .await
+        .unwrap();
+    assert!(stdout.contains("errors: 0, replies: 1"));
+    assert!(process.wait().await.unwrap().success());
+}
+
+#[tokio::test]
+async fn test_exec_interactive_wait_closes_stdin() {
+    init_logger();
+
+    let container = DockerContainer::new(redis_image()).await.unwrap();
+    let process = container.exec_interactive(ExecRequest::new(vec!["cat"])).unwrap();
+    let status = tokio::time::timeout(Duration::from_secs(10), process.wait())
+        .await
+        .expect("cat did not see the end of its input");
+    assert!(status.unwrap().success());
+}
+
+#[tokio::test]
+async fn test_exec_wait_without_taking_output() {
+    init_logger();
+
+    let container = DockerContainer::new(redis_image()).await.unwrap();
+    // Prints far more than a pipe buffers, then exits with a distinct code.
+    let process = container
+        .exec_streaming(ExecRequest::new(vec![
+            "sh",
+            "-c",
+            "seq 1 200000; seq 1 200000 >&2; exit 3",
+        ]))
+        .unwrap();
+    assert_eq!(process.wait().await.unwrap().code(), 3);
+}
+
+#[tokio::test]
+async fn test_exec_in_stopped_container() {
+    init_logger();
+
+    let container = DockerContainer::create(redis_image()).await.unwrap();
+    let result = container.exec(ExecRequest::new(vec!["true"])).await;
+    match result {
+        Err(DockerError::CommandFailed { stderr, .. }) => assert!(stderr.contains("is not running")),
+        other => panic!("expected the daemon to reject the exec, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_copy_to() {
+    init_logger();
+
+    let container = DockerContainer::create(redis_image()).await.unwrap();
+    container
+        .copy_to(
+            CopySource::bytes("#!/bin/sh\necho copied\n").with_mode(0o755),
+            "/usr/local/bin/copied.sh",
+        )
+        .await
+        .unwrap();
+    container.start().await.unwrap();
+    container.wait_until_ready().await.unwrap();
+
+    let output = container.exec(ExecRequest::new(vec!["copied.sh"])).await.unwrap();
+    assert_eq!(output.stdout_lossy(), "copied\n");
+
+    container
+        .copy_to(std::path::Path::new("src"), "/tmp/src")
+        .await
+        .unwrap();
+    let output = container
+        .exec(ExecRequest::new(vec!["ls", "/tmp/src/lib.rs"]))
+        .await
+        .unwrap();
+    assert!(output.success());
+
+    // Ids wider than the tar header fields are rejected instead of producing a corrupt archive.
+    let oversized_owner = container
+        .copy_to(CopySource::bytes("data").with_owner(0o10000000, 0), "/tmp/data")
+        .await;
+    assert!(matches!(oversized_owner, Err(DockerError::InvalidArgument(_))));
+}
+
+#[tokio::test]
+async fn test_copy_from() {
+    init_logger();
+
+    let container = DockerContainer::new(redis_image()).await.unwrap();
+    container
+        .copy_to(CopySource::bytes("report"), "/tmp/report.txt")
+        .await
+        .unwrap();
+    assert_eq!(container.copy_from("/tmp/report.txt").await.unwrap(), b"report");
+    assert!(container.copy_from("/tmp").await.is_err());
+
+    let host_dir = std::env::temp_dir().join(format!("hotto-docker-{}", container.id()));
+    container.copy_from_to_path("/tmp", &host_dir).await.unwrap();
+    assert_eq!(std::fs::read(host_dir.join("report.txt")).unwrap(), b"report");
+    std::fs::remove_dir_all(host_dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_fixed_port_binding() {
+    init_logger();
+
+    let image = redis_image()
+        .with_exposed_port(7000)
+        .with_port_binding(PortBinding::fixed(16379, 6379))
+        .without_publish_all_ports();
+    let container = DockerContainer::new(image).await.unwrap();
+    assert_eq!(container.get_host_port(6379).await, Some(16379));
+    assert_eq!(container.get_host_port(7000).await, None);
+}
+
+#[tokio::test]
+async fn test_get_host_address() {
+    init_logger();
+
+    let container = DockerContainer::new(redis_image()).await.unwrap();
+    let address = container.get_host_address(6379).await.unwrap();
+    assert_eq!(address.ip(), container.host());
+    std::net::TcpStream::connect(address).unwrap();
+}
+
+#[tokio::test]
+async fn test_ip_address() {
+    init_logger();
+
+    let container = DockerContainer::new(redis_image()).await.unwrap();
+    assert!(container.ip_address("bridge").await.unwrap().is_some());
+    assert_eq!(container.ip_address("missing").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_inspect() {
+    init_logger();
+
+    let container = DockerContainer::new(redis_image()).await.unwrap();
+    let info = container.inspect().await.unwrap();
+    assert_eq!(info.id, container.id());
+    assert!(info.state.running);
+    assert_eq!(info.config.image, "redis:5-alpine");
+}
+
+#[tokio::test]
+async fn test_network() {
+    init_logger();
+
+    let network = DockerNetwork::new(NetworkOptions::new("hotto-test").with_label("test", "network"))
+        .await
+        .unwrap();
+    let server = DockerContainer::new(redis_image().with_network(network.name()).with_network_alias("cache"))
+        .await
+        .unwrap();
+    let client = DockerContainer::new(redis_image()).await.unwrap();
+    client.connect_to_network(network.name(), &["client"]).await.unwrap();
+
+    let output = client
+        .exec(ExecRequest::new(vec!["redis-cli", "-h", "cache", "ping"]))
+        .await
+        .unwrap();
+    assert_eq!(output.stdout_lossy(), "PONG\n");
+    assert!(server.ip_address(network.name()).await.unwrap().is_some());
+
+    client.disconnect_from_network(network.name()).await.unwrap();
+    drop(client);
+    drop(server);
+}
+
+#[tokio::test]
+async fn test_volume() {
+    init_logger();
+
+    let volume = DockerVolume::new(VolumeOptions::new("hotto-test")).await.unwrap();
+    let image = redis_image().with_mount(volume.mount("/data"));
+
+    let container = DockerContainer::new(image.clone()).await.unwrap();
+    container
+        .exec(ExecRequest::new(vec!["redis-cli", "SET", "key", "persisted"]))
+        .await
+        .unwrap();
+    container
+        .exec(ExecRequest::new(vec!["redis-cli", "SAVE"]))
+        .await
+        .unwrap();
+    drop(container);
+
+    let container = DockerContainer::new(image).await.unwrap();
+    let output = container
+        .exec(ExecRequest::new(vec!["redis-cli", "GET", "key"]))
+        .await
+        .unwrap();
+    assert_eq!(output.stdout_lossy(), "persisted\n");
+}
+
+#[tokio::test]
+async fn test_name_hostname_and_labels() {
+    init_logger();
+
+    let image = redis_image()
+        .with_container_name("redis")
+        .with_hostname("cache.local")
+        .with_label("team", "platform");
+    let first = DockerContainer::new(image.clone()).await.unwrap();
+    let second = DockerContainer::new(image).await.unwrap();
+
+    let name = first.name().await.unwrap();
+    assert!(name.starts_with("redis-"));
+    assert_ne!(name, second.name().await.unwrap());
+
+    let info = first.inspect().await.unwrap();
+    assert_eq!(info.config.hostname, "cache.local");
+    assert_eq!(info.config.labels["team"], "platform");
+    assert_eq!(info.config.labels["org.hotto-docker.managed"], "true");
+}
+
+#[tokio::test]
+async fn test_entrypoint_workdir_and_user() {
+    init_logger();
+
+    let image = GenericImage::new("redis:5-alpine")
+        .with_entrypoint("sh")
+        .with_workdir("/tmp")
+        .with_user("nobody")
+        .with_args(vec!["-c".to_owned(), "echo $(whoami) $(pwd)".to_owned()]);
+    let output = run_once(&image).await.unwrap();
+    assert_eq!(output.stdout_lossy(), "nobody /tmp\n");
+}
+
+#[tokio::test]
+async fn test_resource_limits_and_security_options() {
+    init_logger();
+
+    let image = redis_image()
+        .with_resource_limits(
+            ResourceLimits::default()
+                .with_memory(128 * 1024 * 1024)
+                .with_pids_limit(64)
+                .with_ulimit(Ulimit::new("nofile", 1024, 2048)),
+        )
+        .with_security_options(
+            SecurityOptions::default()
+                .read_only()
+                .with_cap_drop("ALL")
+                .with_security_opt("no-new-privileges"),
+        )
+        .with_sysctl("net.core.somaxconn", "1024")
+        .with_mount(Mount::tmpfs("/data"))
+        .with_init()
+        // Running as root makes the entrypoint chown /data and switch users, which needs the dropped capabilities.
+        .with_user("redis");
+    let container = DockerContainer::new(image).await.unwrap();
+
+    let info = container.inspect().await.unwrap();
+    assert_eq!(info.host_config.memory, 128 * 1024 * 1024);
+    assert_eq!(info.host_config.pids_limit, Some(64));
+    assert!(info.host_config.readonly_rootfs);
+    assert_eq!(info.host_config.cap_drop, vec!["ALL".to_owned()]);
+
+    // Only the tmpfs is writable on the read-only root filesystem.
+    let outside = container
+        .exec(ExecRequest::new(vec!["touch", "/outside"]))
+        .await
+        .unwrap();
+    assert!(!outside.success());
+    let inside = container
+        .exec(ExecRequest::new(vec!["touch", "/data/inside"]))
+        .await
+        .unwrap();
+    assert!(inside.success());
+
+    let invalid = redis_image().with_resource_limits(ResourceLimits::default().with_cpus(0.0));
+    assert!(DockerContainer::new(invalid).await.is_err());
+}
+
+#[tokio::test]
+async fn test_extra_hosts_and_dns() {
+    init_logger();
+
+    let image = GenericImage::new("alpine:3.11")
+        .with_host_gateway()
+        .with_extra_host("mock.local", "10.10.10.10")
+        .with_dns("1.1.1.1")
+        .with_dns_search("example.com")
+        .with_args(vec![
+            "cat".to_owned(),
+            "/etc/hosts".to_owned(),
+            "/etc/resolv.conf".to_owned(),
+        ]);
+    let output = run_once(&image).await.unwrap().stdout_lossy();
+    assert!(output.contains(HOST_GATEWAY_HOSTNAME));
+    assert!(output.contains("10.10.10.10\tmock.local"));
+    assert!(output.contains("nameserver 1.1.1.1"));
+    assert!(output.contains("search example.com"));
+}
+
+#[tokio::test]
+async fn test_host_tunnel() {
+    init_logger();
+
+    // Only answers once both connections are open, so the tunnel has to carry them at the same time.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    std::thread::spawn(move || {
+        use std::io::Write;
+        let (mut first, _) = listener.accept().unwrap();
+        let (mut second, _) = listener.accept().unwrap();
+        first.write_all(b"pong\n").unwrap();
+        second.write_all(b"pong\n").unwrap();
+    });
+
+    let network = DockerNetwork::new(NetworkOptions::default()).await.unwrap();
+    let tunnel = HostTunnel::new(network.name(), &[port]).await.unwrap();
+    let connect = format!("sleep 5 | nc {} {}", tunnel.hostname(), port);
+    let image = GenericImage::new("alpine:3.11")
+        .with_network(network.name())
+        .with_args(vec![
+            "sh".to_owned(),
+            "-c".to_owned(),
+            format!("({}) & ({}) & wait", connect, connect),
+        ]);
+    let output = run_once(&image).await.unwrap();
+    assert_eq!(output.stdout_lossy(), "pong\npong\n");
+}
+
+#[tokio::test]
+async fn test_restart_policy_and_stop_settings() {
+    init_logger();
+
+    let image = redis_image()
+        .with_restart_policy(RestartPolicy::OnFailure { max_retries: Some(3) })
+        .with_stop_signal("SIGINT")
+        .with_stop_timeout(Duration::from_secs(5));
+    let container = DockerContainer::new(image).await.unwrap();
+
+    let info = container.inspect().await.unwrap();
+    assert_eq!(info.host_config.restart_policy.name, "on-failure");
+    assert_eq!(info.host_config.restart_policy.maximum_retry_count, 3);
+    assert_eq!(info.config.stop_signal.as_deref(), Some("SIGINT"));
+    assert_eq!(info.config.stop_timeout, Some(5));
+
+    let sub_second = DockerContainer::new(redis_image().with_stop_timeout(Duration::from_millis(500)))
+        .await
+        .unwrap();
+    assert_eq!(sub_second.inspect().await.unwrap().config.stop_timeout, Some(1));
+}
+
+#[tokio::test]
+async fn test_platform_mismatch() {
+    init_logger();
+
+    let image = GenericImage::new("alpine:3.11").with_args(vec!["true".to_owned()]);
+    run_once(&image).await.unwrap();
+    let info = ImageInspectCommand::inspect_image("alpine:3.11")
+        .await
+        .unwrap()
+        .unwrap();
+    let foreign = if info.architecture == "s390x" {
+        "linux/amd64"
+    } else {
+        "linux/s390x"
+    };
+
+    // Without pulling, the cached image for the native platform cannot be used.
+    let mismatched = image.with_platform(foreign).with_pull_policy(PullPolicy::Never);
+    match run_once(&mismatched).await {
+        Err(DockerError::PlatformMismatch { requested, actual, .. }) => {
+            assert_eq!(requested, foreign);
+            assert_eq!(actual, info.platform());
+        }
+        other => panic!(
+            "expected a platform mismatch, got {:?}",
+            other.map(|output| output.exit_code)
+        ),
+    }
+
+    // By default an image cached for another platform counts as missing and is pulled for the requested one.
+    let puller = ImagePuller::new(PullPolicy::IfMissing).with_platform(foreign);
+    puller.apply("busybox:1.31").await.unwrap();
+    let pulled = ImageInspectCommand::inspect_image("busybox:1.31")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(pulled.matches_platform(foreign));
+    assert!(!puller.apply("busybox:1.31").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_image_puller() {
+    init_logger();
+
+    let updates = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+    let recorded = updates.clone();
+    let puller = ImagePuller::new(PullPolicy::Always)
+        .on_progress(move |progress| recorded.lock().unwrap().push(progress.clone()));
+    assert!(puller.apply("alpine:3.11").await.unwrap());
+    assert!(!updates.lock().unwrap().is_empty());
+
+    assert!(!ImagePuller::new(PullPolicy::IfMissing)
+        .apply("alpine:3.11")
+        .await
+        .unwrap());
+    match ImagePuller::new(PullPolicy::Never).apply("alpine:0.0-missing").await {
+        Err(DockerError::ImageNotFound(image)) => assert_eq!(image, "alpine:0.0-missing"),
+        other => panic!("expected a missing image, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_prepull() {
+    init_logger();
+
+    let images = vec![
+        GenericImage::new("alpine:3.11"),
+        GenericImage::new("redis:5-alpine"),
+        GenericImage::new("alpine:3.11"),
+        GenericImage::new("alpine:0.0-missing"),
+    ];
+    match prepull(&images).await {
+        Err(DockerError::PullFailed(failures)) => {
+            assert_eq!(failures.len(), 1);
+            assert_eq!(failures[0].0, "alpine:0.0-missing");
+        }
+        other => panic!("expected a single failed pull, got {:?}", other),
+    }
+    assert_eq!(prepull(&images[..2]).await.unwrap(), Vec::<String>::new());
+}
+
+#[tokio::test]
+async fn test_attach() {
+    init_logger();
+
+    let image = GenericImage::new("alpine:3.11")
+        .with_stdin_open()
+        .with_args(vec!["cat".to_owned()]);
+    let container = DockerContainer::create(image).await.unwrap();
+    container.start().await.unwrap();
+
+    let mut process = container.attach().unwrap();
+    let mut stdin = process.take_stdin().unwrap();
+    stdin.write_all(b"hello\n").await.unwrap();
+    let mut lines = tokio::io::BufReader::new(process.take_stdout().unwrap()).lines();
+    assert_eq!(lines.next_line().await.unwrap(), Some("hello".to_owned()));
+
+    drop(stdin);
+    assert!(container
+        .wait_for_exit(Duration::from_secs(10))
+        .await
+        .unwrap()
+        .success());
+}
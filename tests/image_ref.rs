@@ -0,0 +1,59 @@
+use hotto_docker::*;
+
+#[test]
+fn test_image_ref_normalization() {
+    let redis = ImageRef::parse("redis").unwrap();
+    assert_eq!(redis.registry(), "docker.io");
+    assert_eq!(redis.repository(), "library/redis");
+    assert_eq!(redis.namespace(), Some("library"));
+    assert_eq!(redis.name(), "redis");
+    assert_eq!(redis.tag(), Some("latest"));
+    assert_eq!(redis.to_string(), "docker.io/library/redis:latest");
+    assert!(redis.is_docker_hub());
+
+    let hub = ImageRef::parse("index.docker.io/bitnami/redis:6.0").unwrap();
+    assert_eq!(hub.to_string(), "docker.io/bitnami/redis:6.0");
+
+    let digest = format!("sha256:{}", "a".repeat(64));
+    let local = ImageRef::parse(&format!("localhost:5000/team/tools/cli:1.2.3@{}", digest)).unwrap();
+    assert_eq!(local.registry(), "localhost:5000");
+    assert_eq!(local.repository(), "team/tools/cli");
+    assert_eq!(local.namespace(), Some("team/tools"));
+    assert_eq!(local.tag(), Some("1.2.3"));
+    assert_eq!(local.digest(), Some(digest.as_str()));
+    assert!(!local.is_docker_hub());
+
+    let pinned: ImageRef = format!("alpine@{}", digest).parse().unwrap();
+    assert_eq!(pinned.tag(), None);
+}
+
+#[test]
+fn test_image_ref_validation() {
+    for reference in &[
+        "",
+        "Redis",
+        "redis:",
+        "redis:-latest",
+        "redis/",
+        "my..repo",
+        "my-repo-",
+        "localhost:99999/redis",
+        "redis@sha256:abc",
+        "bad host.io/redis",
+    ] {
+        assert!(ImageRef::parse(reference).is_err(), "{} should be invalid", reference);
+    }
+    for reference in &[
+        "my_repo",
+        "my__repo",
+        "my--repo",
+        "a.b/c-d/e_f:1.0_rc-1",
+        "[::1]:5000/redis",
+    ] {
+        assert!(ImageRef::parse(reference).is_ok(), "{} should be valid", reference);
+    }
+
+    let err = GenericImage::try_new("Redis:5").err().unwrap();
+    assert!(err.to_string().contains("`Redis` which must be lowercase"), "{}", err);
+    assert!(GenericImage::try_new("redis:5-alpine").is_ok());
+}
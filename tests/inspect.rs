@@ -0,0 +1,79 @@
+use hotto_docker::*;
+
+#[test]
+fn test_container_info_from_inspect_output() {
+    let info: ContainerInfo = serde_json::from_str(
+        r#"{
+            "Id": "4fa6e0f0c678",
+            "Name": "/redis",
+            "Platform": "linux",
+            "State": {
+                "Status": "running",
+                "Running": true,
+                "OOMKilled": false,
+                "Pid": 1234,
+                "ExitCode": 0,
+                "StartedAt": "2020-02-10T10:00:00.000000000Z",
+                "Health": { "Status": "healthy", "FailingStreak": 0, "Log": null }
+            },
+            "Config": {
+                "Env": ["PATH=/usr/bin", "EMPTY"],
+                "Cmd": ["redis-server"],
+                "Entrypoint": null,
+                "Image": "redis:5-alpine",
+                "Labels": null
+            },
+            "HostConfig": { "CapAdd": null, "Memory": 268435456, "RestartPolicy": { "Name": "on-failure", "MaximumRetryCount": 3 } },
+            "Mounts": [{ "Type": "volume", "Name": "data", "Source": "/var/lib/docker/volumes/data/_data", "Destination": "/data", "RW": true }],
+            "NetworkSettings": {
+                "Ports": null,
+                "Networks": {
+                    "test-net": { "IPAddress": "172.18.0.2", "Gateway": "172.18.0.1", "Aliases": ["redis"], "MacAddress": "02:42:ac:12:00:02" }
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(info.name, "/redis");
+    assert!(info.state.running);
+    assert_eq!(info.state.pid, 1234);
+    assert_eq!(info.state.health.as_ref().unwrap().status, "healthy");
+    assert_eq!(info.config.env_vars()["PATH"], "/usr/bin");
+    assert_eq!(info.config.env_vars()["EMPTY"], "");
+    assert!(info.config.entrypoint.is_empty());
+    assert!(info.config.labels.is_empty());
+    assert_eq!(info.host_config.memory, 256 * 1024 * 1024);
+    assert_eq!(info.host_config.restart_policy.maximum_retry_count, 3);
+    assert_eq!(info.mounts[0].name.as_deref(), Some("data"));
+    assert_eq!(info.get_ports(), Ports::default());
+
+    let network = &info.networks()["test-net"];
+    assert_eq!(network.ip(), Some("172.18.0.2".parse().unwrap()));
+    assert_eq!(network.gateway(), Some("172.18.0.1".parse().unwrap()));
+    assert_eq!(network.aliases(), ["redis".to_owned()]);
+}
+
+#[test]
+fn test_image_info_platform() {
+    let info: ImageInfo = serde_json::from_str(
+        r#"{
+            "Id": "sha256:4fa6e0f0c678",
+            "RepoTags": ["redis:5-alpine"],
+            "RepoDigests": null,
+            "Os": "linux",
+            "Architecture": "arm",
+            "Variant": "v7",
+            "Size": 29804012
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(info.platform(), "linux/arm/v7");
+    assert!(info.matches_platform("linux"));
+    assert!(info.matches_platform("linux/arm"));
+    assert!(info.matches_platform("linux/arm/v7"));
+    assert!(!info.matches_platform("linux/arm/v6"));
+    assert!(!info.matches_platform("linux/amd64"));
+    assert!(info.repo_digests.is_empty());
+}
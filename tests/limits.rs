@@ -0,0 +1,21 @@
+use hotto_docker::*;
+
+#[test]
+fn test_resource_limits_to_args() {
+    let limits = ResourceLimits::default()
+        .with_memory(128 * 1024 * 1024)
+        .with_cpus(1.5)
+        .with_ulimit(Ulimit::new("nofile", 1024, 2048));
+    assert_eq!(
+        limits.to_args().unwrap(),
+        vec!["--memory", "134217728", "--cpus", "1.5", "--ulimit", "nofile=1024:2048"]
+    );
+    assert!(ResourceLimits::default().to_args().unwrap().is_empty());
+
+    for cpus in &[0.0, -1.0, f64::NAN] {
+        match ResourceLimits::default().with_cpus(*cpus).to_args() {
+            Err(DockerError::InvalidArgument(reason)) => assert!(reason.contains("cpus"), "{}", reason),
+            other => panic!("expected {} cpus to be rejected, got {:?}", cpus, other),
+        }
+    }
+}
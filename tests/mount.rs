@@ -0,0 +1,39 @@
+use hotto_docker::*;
+
+#[test]
+fn test_mount_to_arg() {
+    let bind: Mount = Mount::bind("/host/data", "/data")
+        .read_only()
+        .with_propagation(BindPropagation::RShared)
+        .into();
+    assert_eq!(
+        bind.to_arg().unwrap(),
+        "type=bind,source=/host/data,target=/data,readonly,bind-propagation=rshared"
+    );
+
+    let volume: Mount = Mount::volume("nfs-data", "/data")
+        .with_driver("local")
+        .with_driver_opt("type", "nfs")
+        .with_driver_opt("o", "addr=10.0.0.1,rw")
+        .into();
+    assert_eq!(
+        volume.to_arg().unwrap(),
+        "type=volume,source=nfs-data,target=/data,volume-driver=local,\"volume-opt=o=addr=10.0.0.1,rw\",volume-opt=type=nfs"
+    );
+
+    let tmpfs: Mount = Mount::tmpfs("/run")
+        .with_size(64 * 1024 * 1024)
+        .with_mode(0o1777)
+        .into();
+    assert_eq!(
+        tmpfs.to_arg().unwrap(),
+        "type=tmpfs,target=/run,tmpfs-size=67108864,tmpfs-mode=1777"
+    );
+}
+
+#[test]
+fn test_mount_validation() {
+    assert!(Mount::from(Mount::bind("relative/data", "/data")).to_arg().is_err());
+    assert!(Mount::from(Mount::tmpfs("run")).to_arg().is_err());
+    assert!(Mount::from(Mount::volume("a/b", "/data")).to_arg().is_err());
+}
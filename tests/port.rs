@@ -0,0 +1,52 @@
+use hotto_docker::*;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+#[test]
+fn test_port_binding_to_arg() {
+    assert_eq!(PortBinding::new(9092).to_arg(), "9092/tcp");
+    assert_eq!(PortBinding::fixed(9092, 9092).to_arg(), "9092:9092/tcp");
+    assert_eq!(
+        PortBinding::new(ExposedPort::udp(53))
+            .with_host_ip(IpAddr::V4(Ipv4Addr::LOCALHOST))
+            .to_arg(),
+        "127.0.0.1::53/udp"
+    );
+    assert_eq!(
+        PortBinding::fixed(9042, 9042)
+            .with_host_ip(IpAddr::V6(Ipv6Addr::LOCALHOST))
+            .to_arg(),
+        "[::1]:9042:9042/tcp"
+    );
+}
+
+#[test]
+fn test_ports_from_container_info() {
+    let info: ContainerInfo = serde_json::from_str(
+        r#"{
+            "Id": "abc",
+            "State": { "Status": "running", "ExitCode": 0 },
+            "NetworkSettings": {
+                "Ports": {
+                    "53/tcp": [{ "HostIp": "0.0.0.0", "HostPort": "32768" }],
+                    "53/udp": [
+                        { "HostIp": "::", "HostPort": "32770" },
+                        { "HostIp": "0.0.0.0", "HostPort": "32769" }
+                    ],
+                    "8080/tcp": null
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+    let ports = info.get_ports();
+
+    assert_eq!(ports.map_to_host_port(53), Some(32768));
+    assert_eq!(ports.map_to_host_port_udp(53), Some(32769));
+    assert_eq!(
+        ports.map_to_host_port_preferring(ExposedPort::udp(53), IpVersion::V6),
+        Some(32770)
+    );
+    assert_eq!(ports.bindings(ExposedPort::udp(53)).len(), 2);
+    assert_eq!(ports.map_to_host_port(8080), None);
+    assert_eq!(ports.iter().count(), 2);
+}
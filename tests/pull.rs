@@ -0,0 +1,39 @@
+use hotto_docker::*;
+
+/// The output of `docker pull alpine:3.11` when it is not written to a terminal.
+const PULL_OUTPUT: &str = "3.11: Pulling from library/alpine
+c9b1b535fdd9: Pulling fs layer
+c9b1b535fdd9: Verifying Checksum
+c9b1b535fdd9: Download complete
+c9b1b535fdd9: Pull complete
+Digest: sha256:ab00606a42621fb68f2ed6ad3c88be54397f981a7b70a79db3d1172b11c4367d
+Status: Downloaded newer image for alpine:3.11
+docker.io/library/alpine:3.11
+";
+
+#[test]
+fn test_pull_progress_from_pull_output() {
+    let updates: Vec<_> = PULL_OUTPUT.lines().map(PullProgress::parse).collect();
+    assert_eq!(updates.len(), 8);
+
+    let layer = |status: &str| PullProgress {
+        layer: Some("c9b1b535fdd9".to_owned()),
+        status: status.to_owned(),
+    };
+    assert_eq!(
+        &updates[1..5],
+        &[
+            layer("Pulling fs layer"),
+            layer("Verifying Checksum"),
+            layer("Download complete"),
+            layer("Pull complete"),
+        ]
+    );
+
+    // Lines about the whole image keep their prefix, which is a tag, digest label or status label.
+    assert_eq!(updates[0].layer, None);
+    assert_eq!(updates[0].status, "3.11: Pulling from library/alpine");
+    assert_eq!(updates[6].layer, None);
+    assert_eq!(updates[6].status, "Status: Downloaded newer image for alpine:3.11");
+    assert_eq!(updates[7].status, "docker.io/library/alpine:3.11");
+}